@@ -0,0 +1,398 @@
+use crate::cli::{ErrorFormat, GlobalArgs, OnConflict, ProgressMode};
+use crate::display::{BatchDisplay, ProgressDisplay};
+use crate::error_output;
+use crate::inputs::Input;
+use crate::naming;
+use crate::report::{change, Report};
+use anyhow::Result;
+use serde_json::json;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use video_express::ffmpeg::cancel;
+use video_express::utils::{format_duration, format_size, is_stdio, is_url, unique_path_by};
+use video_express::{FfmpegRunner, VxError};
+
+pub struct Job {
+    pub input: PathBuf,
+    pub output: PathBuf,
+    pub runner: FfmpegRunner,
+    pub duration: Option<f64>,
+    pub description: String,
+    pub warn_if_larger: bool,
+}
+
+impl Job {
+    pub fn new(
+        verb: &str,
+        input: &Path,
+        output: PathBuf,
+        runner: FfmpegRunner,
+        duration: Option<f64>,
+    ) -> Self {
+        let description = format!(
+            "{verb} {} → {}",
+            naming::source(input).file_name().unwrap_or_default().to_string_lossy(),
+            output.file_name().unwrap_or_default().to_string_lossy()
+        );
+        let input = input.to_path_buf();
+        Self { input, output, runner, duration, description, warn_if_larger: false }
+    }
+
+    /// Warns after the run when the output is bigger than the input, offering to keep the original.
+    pub fn warn_if_larger(mut self) -> Self {
+        self.warn_if_larger = true;
+        self
+    }
+
+    // The guard is held until the output is in place, so a signal never leaves a staging file.
+    fn run(self, display: ProgressDisplay) -> Result<()> {
+        let _signals = cancel::guard();
+        Ok(self.runner.with_progress(display, self.duration).run()?)
+    }
+
+    fn print_warnings(&self, format: ErrorFormat) {
+        for warning in self.runner.warnings() {
+            error_output::warn(warning, format);
+        }
+    }
+
+    fn print_command(mut self) {
+        println!("{}", self.runner.command_line());
+    }
+}
+
+enum Status {
+    Done,
+    Failed(anyhow::Error),
+    Skipped,
+}
+
+struct Outcome {
+    index: usize,
+    input: PathBuf,
+    output: Option<PathBuf>,
+    status: Status,
+    elapsed: Duration,
+}
+
+impl Outcome {
+    fn planning_failed(index: usize, input: &Path, err: anyhow::Error) -> Self {
+        let status = match is_skip(&err) {
+            true => Status::Skipped,
+            false => Status::Failed(err),
+        };
+        Self { index, input: input.to_path_buf(), output: None, status, elapsed: Duration::ZERO }
+    }
+}
+
+// Declining an overwrite prompt or --skip-existing skips that file rather than failing the batch.
+fn is_skip(err: &anyhow::Error) -> bool {
+    matches!(err.downcast_ref(), Some(VxError::Cancelled | VxError::OutputExists(_)))
+}
+
+/// Plans every input, then encodes up to `--jobs` files at a time. A single input behaves
+/// exactly like before batching existed; with several, failures are collected and summarized.
+pub fn run<F>(inputs: Vec<Input>, global: &GlobalArgs, plan: F) -> Result<()>
+where
+    F: Fn(&Input) -> Result<Job>,
+{
+    if let [input] = inputs.as_slice() {
+        let job = match plan(input) {
+            Err(err) if matches!(err.downcast_ref(), Some(VxError::OutputExists(_))) => {
+                print_skipped(input, &err, global.progress);
+                return Ok(());
+            }
+            job => job?,
+        };
+        job.print_warnings(global.error_format);
+        if global.dry_run {
+            job.print_command();
+            return Ok(());
+        }
+
+        let (input, output) = (job.input.clone(), job.output.clone());
+        let (duration, warn_if_larger) = (job.duration, job.warn_if_larger);
+        let display = ProgressDisplay::new(&job.description, global.progress)
+            .on_stderr(is_stdio(&output));
+        let started = Instant::now();
+        job.run(display)?;
+
+        // Pipes and network streams have no size to compare.
+        if is_stdio(&input) || is_url(&input) || is_stdio(&output) {
+            return Ok(());
+        }
+
+        let elapsed = started.elapsed();
+        let report = Report { input: &input, output: &output, elapsed, duration };
+        return report.print(global, warn_if_larger);
+    }
+
+    // Outputs claimed so far, so two inputs can't be planned onto the same file.
+    let mut planned = HashSet::new();
+
+    if global.dry_run {
+        for input in &inputs {
+            match plan(input).and_then(|job| reserve(job, &mut planned, global)) {
+                Ok(job) => {
+                    job.print_warnings(global.error_format);
+                    job.print_command();
+                }
+                Err(err) if is_skip(&err) => println!("# {err}"),
+                Err(err) => return Err(err),
+            }
+        }
+        return Ok(());
+    }
+
+    let mut outcomes = Vec::new();
+    let mut jobs = Vec::new();
+    for (index, input) in inputs.iter().enumerate() {
+        match plan(input).and_then(|job| reserve(job, &mut planned, global)) {
+            Ok(job) => {
+                job.print_warnings(global.error_format);
+                jobs.push((index, job));
+            }
+            Err(err) => outcomes.push(Outcome::planning_failed(index, &input.path, err)),
+        }
+    }
+
+    let started = Instant::now();
+    let batch = BatchDisplay::new(jobs.len(), global.progress);
+    let workers = (global.jobs as usize).clamp(1, jobs.len().max(1));
+    let queue = Mutex::new(jobs.into_iter());
+    let finished = Mutex::new(outcomes);
+    let cancelled = AtomicBool::new(false);
+    // Covers the gaps between jobs too, so a Ctrl-C is never forgotten before workers see it.
+    let signals = cancel::guard();
+
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                while !cancelled.load(Ordering::SeqCst) {
+                    let Some((index, job)) = queue.lock().unwrap().next() else {
+                        break;
+                    };
+
+                    let input = job.input.clone();
+                    let output = job.output.clone();
+                    let display = ProgressDisplay::new(&job.description, global.progress)
+                        .in_batch(&batch, &input);
+                    let job_started = Instant::now();
+
+                    let status = match job.run(display) {
+                        Ok(()) => Status::Done,
+                        Err(err) if matches!(err.downcast_ref(), Some(VxError::Cancelled)) => {
+                            cancelled.store(true, Ordering::SeqCst);
+                            Status::Skipped
+                        }
+                        Err(err) => Status::Failed(err),
+                    };
+                    batch.job_done();
+
+                    finished.lock().unwrap().push(Outcome {
+                        index,
+                        input,
+                        output: Some(output),
+                        status,
+                        elapsed: job_started.elapsed(),
+                    });
+                }
+            });
+        }
+    });
+    drop(signals);
+    batch.finish();
+
+    let mut outcomes = finished.into_inner().unwrap();
+    for (index, job) in queue.into_inner().unwrap() {
+        let (input, status, elapsed) = (job.input, Status::Skipped, Duration::ZERO);
+        outcomes.push(Outcome { index, input, output: None, status, elapsed });
+    }
+    outcomes.sort_by_key(|outcome| outcome.index);
+
+    let cancelled = cancelled.into_inner();
+    report(&outcomes, started.elapsed(), global, cancelled);
+
+    if cancelled {
+        return Err(VxError::Cancelled.into());
+    }
+
+    let failed = outcomes.iter().filter(|o| matches!(o.status, Status::Failed(_))).count();
+    if failed > 0 {
+        return Err(VxError::BatchFailed { failed, total: outcomes.len() }.into());
+    }
+
+    Ok(())
+}
+
+// Different inputs can map to one output, like clip.mov and clip.mp4 both making clip.gif.
+// Running both would let the later job silently replace the earlier one's result, so with
+// --on-conflict rename the later one moves to a name no other job has claimed.
+fn reserve(mut job: Job, planned: &mut HashSet<PathBuf>, global: &GlobalArgs) -> Result<Job> {
+    if is_stdio(&job.output) || planned.insert(std::path::absolute(&job.output)?) {
+        return Ok(job);
+    }
+    if global.on_conflict != Some(OnConflict::Rename) {
+        return Err(VxError::DuplicateOutput(job.output).into());
+    }
+
+    let taken = |path: &Path| {
+        path.exists() || std::path::absolute(path).is_ok_and(|p| planned.contains(&p))
+    };
+    let output = unique_path_by(&job.output, taken);
+    planned.insert(std::path::absolute(&output)?);
+
+    let old_name = job.output.file_name().unwrap_or_default().to_string_lossy().into_owned();
+    let new_name = output.file_name().unwrap_or_default().to_string_lossy().into_owned();
+    if let Some(verb_and_input) = job.description.strip_suffix(old_name.as_str()) {
+        job.description = format!("{verb_and_input}{new_name}");
+    }
+    job.runner = job.runner.output(&output);
+    job.output = output;
+    Ok(job)
+}
+
+fn file_size(path: Option<&Path>) -> Option<u64> {
+    path.and_then(|p| std::fs::metadata(p).ok()).map(|m| m.len())
+}
+
+fn print_skipped(input: &Input, err: &anyhow::Error, mode: ProgressMode) {
+    let Some(VxError::OutputExists(output)) = err.downcast_ref() else {
+        return;
+    };
+    match mode {
+        ProgressMode::Json => {
+            let event = json!({
+                "event": "skipped",
+                "input": input.path.to_string_lossy(),
+                "output": output.to_string_lossy(),
+            });
+            println!("{event}");
+        }
+        ProgressMode::Auto | ProgressMode::Plain => println!("{err}"),
+    }
+}
+
+fn report(outcomes: &[Outcome], elapsed: Duration, global: &GlobalArgs, cancelled: bool) {
+    let count = |f: fn(&Status) -> bool| outcomes.iter().filter(|o| f(&o.status)).count();
+    let done = count(|s| matches!(s, Status::Done));
+    let failed = count(|s| matches!(s, Status::Failed(_)));
+    let skipped = count(|s| matches!(s, Status::Skipped));
+
+    let sizes: Vec<(Option<u64>, Option<u64>)> = outcomes
+        .iter()
+        .map(|o| match o.status {
+            Status::Done => (file_size(Some(&o.input)), file_size(o.output.as_deref())),
+            _ => (file_size(Some(&o.input)), None),
+        })
+        .collect();
+    let (total_in, total_out) = outcomes
+        .iter()
+        .zip(&sizes)
+        .filter(|(o, _)| matches!(o.status, Status::Done))
+        .fold((0, 0), |(i, o), (_, (a, b))| (i + a.unwrap_or(0), o + b.unwrap_or(0)));
+
+    if let ProgressMode::Json = global.progress {
+        for outcome in outcomes {
+            if let Status::Failed(ref err) = outcome.status {
+                let kind = error_output::kind(err);
+                let event = json!({
+                    "event": "error",
+                    "input": outcome.input.to_string_lossy(),
+                    "kind": kind.as_str(),
+                    "exit_code": kind.exit_code(),
+                    "message": format!("{err:#}"),
+                });
+                println!("{event}");
+                error_output::print(err, Some(&outcome.input), global.error_format);
+            }
+        }
+        let event = json!({
+            "event": "summary",
+            "total": outcomes.len(),
+            "succeeded": done,
+            "failed": failed,
+            "skipped": skipped,
+            "input_size": total_in,
+            "output_size": total_out,
+            "elapsed": elapsed.as_secs_f64(),
+            "cancelled": cancelled,
+        });
+        println!("{event}");
+        return;
+    }
+
+    let rows: Vec<[String; 4]> = outcomes
+        .iter()
+        .zip(&sizes)
+        .map(|(outcome, (input_size, output_size))| {
+            let status = match outcome.status {
+                Status::Done => "done",
+                Status::Failed(_) => "failed",
+                Status::Skipped => "skipped",
+            };
+            let size = match (input_size, output_size) {
+                (Some(i), Some(o)) => {
+                    format!("{} → {}{}", format_size(*i), format_size(*o), change(*i, *o))
+                }
+                (Some(i), None) => format_size(*i),
+                _ => "-".to_string(),
+            };
+            let time = match outcome.status {
+                Status::Skipped => "-".to_string(),
+                _ => format_duration(outcome.elapsed.as_secs_f64()),
+            };
+            [outcome.input.display().to_string(), status.to_string(), size, time]
+        })
+        .collect();
+
+    let header = ["Input", "Status", "Size", "Time"];
+    let mut widths = header.map(|h| h.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let line = |cells: [&str; 4]| {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell}{}", " ".repeat(width - cell.chars().count())))
+            .collect();
+        println!("{}", padded.join("  ").trim_end());
+    };
+
+    println!();
+    line(header);
+    println!("{}", "─".repeat(widths.iter().sum::<usize>() + 2 * (widths.len() - 1)));
+    for row in &rows {
+        line(row.each_ref().map(String::as_str));
+    }
+    println!();
+
+    let mut totals = format!("{} files: {done} done", outcomes.len());
+    if failed > 0 {
+        totals.push_str(&format!(", {failed} failed"));
+    }
+    if skipped > 0 {
+        totals.push_str(&format!(", {skipped} skipped"));
+    }
+    if done > 0 {
+        totals.push_str(&format!(" · {} → {}", format_size(total_in), format_size(total_out)));
+    }
+    totals.push_str(&format!(" · {}", format_duration(elapsed.as_secs_f64())));
+    println!("{totals}");
+
+    for outcome in outcomes {
+        if let Status::Failed(ref err) = outcome.status {
+            if global.error_format == ErrorFormat::Text {
+                eprintln!();
+            }
+            error_output::print(err, Some(&outcome.input), global.error_format);
+        }
+    }
+}
@@ -1,15 +1,134 @@
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
-#[derive(Parser)]
+const EXIT_CODES: &str = "\
+Exit codes:
+  0    success
+  1    other error (e.g. an output conflict)
+  2    invalid arguments, config or preset
+  3    input not found
+  4    unsupported format or codec
+  5    ffmpeg missing, too old, or lacking an encoder/filter
+  6    ffmpeg failed
+  7    some jobs of a batch failed
+  130  cancelled";
+
+#[derive(Clone, Parser)]
 #[command(name = "vx")]
 #[command(version, about = "Simple ffmpeg wrapper for humans")]
+#[command(after_long_help = EXIT_CODES)]
 pub struct Cli {
+    #[command(flatten)]
+    pub global: GlobalArgs,
+
     #[command(subcommand)]
     pub command: Commands,
 }
 
-#[derive(Subcommand)]
+#[derive(Clone, Args)]
+pub struct GlobalArgs {
+    /// Print the ffmpeg command instead of running it
+    #[arg(long, global = true, visible_alias = "print-command")]
+    pub dry_run: bool,
+
+    /// Stream ffmpeg's log output
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
+
+    /// Append the ffmpeg command and full log of every job to this file
+    #[arg(long, global = true, value_name = "PATH")]
+    pub log_file: Option<PathBuf>,
+
+    /// Path to the ffmpeg binary [env: VX_FFMPEG]
+    #[arg(long, global = true, value_name = "PATH")]
+    pub ffmpeg_path: Option<PathBuf>,
+
+    /// Directory for outputs without an explicit -o [default: next to the input]
+    #[arg(long, global = true, value_name = "DIR")]
+    pub out_dir: Option<PathBuf>,
+
+    /// Name outputs from a template, e.g. "{stem}_{width}p.{ext}" (see README for placeholders)
+    #[arg(long, global = true, value_name = "TEMPLATE")]
+    pub output_template: Option<String>,
+
+    /// Descend into subdirectories of directory inputs, mirroring them under --out-dir
+    #[arg(short, long, global = true)]
+    pub recursive: bool,
+
+    /// File extensions to pick up from directories (e.g. mov,mts) [default: common video types]
+    #[arg(long, global = true, value_delimiter = ',', value_name = "EXT")]
+    pub ext: Vec<String>,
+
+    /// What to do when an output already exists [default: ask, or fail without a terminal]
+    #[arg(long, global = true, value_enum, value_name = "MODE")]
+    pub on_conflict: Option<OnConflict>,
+
+    /// Skip inputs whose output already exists (same as --on-conflict skip)
+    #[arg(long, global = true, conflicts_with = "on_conflict")]
+    pub skip_existing: bool,
+
+    /// Number of files to encode in parallel
+    #[arg(
+        short,
+        long,
+        global = true,
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    pub jobs: u32,
+
+    /// Progress output: interactive bar, periodic plain lines, or NDJSON events
+    #[arg(long, global = true, value_enum, default_value = "auto")]
+    pub progress: ProgressMode,
+
+    /// How errors are printed on stderr: readable text, or one JSON object
+    #[arg(long, global = true, value_enum, default_value = "text", value_name = "FORMAT")]
+    pub error_format: ErrorFormat,
+
+    /// Set by `vx watch`, where nobody is around to answer a prompt.
+    #[arg(skip)]
+    pub unattended: bool,
+}
+
+impl GlobalArgs {
+    /// Fills in anything the inner command of `vx watch` did not set from the outer command line.
+    pub fn inherit(&mut self, outer: &GlobalArgs) {
+        self.dry_run |= outer.dry_run;
+        self.verbose |= outer.verbose;
+        self.skip_existing |= outer.skip_existing;
+        self.log_file = self.log_file.take().or_else(|| outer.log_file.clone());
+        self.ffmpeg_path = self.ffmpeg_path.take().or_else(|| outer.ffmpeg_path.clone());
+        self.out_dir = self.out_dir.take().or_else(|| outer.out_dir.clone());
+        self.on_conflict = self.on_conflict.or(outer.on_conflict);
+        self.output_template =
+            self.output_template.take().or_else(|| outer.output_template.clone());
+        if self.ext.is_empty() {
+            self.ext = outer.ext.clone();
+        }
+        if matches!(self.progress, ProgressMode::Auto) {
+            self.progress = outer.progress;
+        }
+        if self.error_format == ErrorFormat::Text {
+            self.error_format = outer.error_format;
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ProgressMode {
+    Auto,
+    Plain,
+    Json,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum ErrorFormat {
+    Text,
+    Json,
+}
+
+#[derive(Clone, Subcommand)]
 pub enum Commands {
     /// Convert video to GIF
     Gif(GifArgs),
@@ -22,24 +141,34 @@ pub enum Commands {
 
     /// Show video information
     Info(InfoArgs),
+
+    /// Manage configuration (~/.config/vx/config.toml and ./vx.toml)
+    Config(ConfigArgs),
+
+    /// Manage named presets
+    Preset(PresetArgs),
+
+    /// Run a command on each new file that appears in a directory
+    Watch(WatchArgs),
 }
 
-#[derive(Parser)]
+#[derive(Clone, Parser)]
 pub struct GifArgs {
-    /// Input video file
-    pub input: PathBuf,
+    /// Input video files, directories or glob patterns
+    #[arg(required = true, value_name = "INPUT")]
+    pub inputs: Vec<PathBuf>,
 
     /// Output file [default: {input_name}.gif]
     #[arg(short, long)]
     pub output: Option<PathBuf>,
 
-    /// Width in pixels
-    #[arg(short, long, default_value = "480")]
-    pub width: u32,
+    /// Width in pixels [default: 480]
+    #[arg(short, long)]
+    pub width: Option<u32>,
 
-    /// Frames per second
-    #[arg(short, long, default_value = "10")]
-    pub fps: u32,
+    /// Frames per second [default: 10]
+    #[arg(short, long)]
+    pub fps: Option<u32>,
 
     /// Start time (e.g., 0:30, 30)
     #[arg(short, long)]
@@ -49,65 +178,286 @@ pub struct GifArgs {
     #[arg(short, long)]
     pub duration: Option<f64>,
 
+    /// Use a saved or built-in preset; explicit flags override it
+    #[arg(long)]
+    pub preset: Option<String>,
+
     /// Overwrite without confirmation
     #[arg(long)]
     pub force: bool,
 }
 
-#[derive(Parser)]
+#[derive(Clone, Parser)]
 pub struct CompressArgs {
-    /// Input video file
-    pub input: PathBuf,
+    /// Input video files, directories or glob patterns
+    #[arg(required = true, value_name = "INPUT")]
+    pub inputs: Vec<PathBuf>,
 
     /// Output file [default: {input_name}_compressed.{ext}]
     #[arg(short, long)]
     pub output: Option<PathBuf>,
 
-    /// Quality level
-    #[arg(short, long, value_enum, default_value = "medium")]
-    pub quality: Quality,
+    /// Quality level [default: medium]
+    #[arg(short, long, value_enum)]
+    pub quality: Option<Quality>,
+
+    /// Constant rate factor, overrides --quality (0-51, lower is better)
+    #[arg(long, value_parser = clap::value_parser!(u8).range(0..=51))]
+    pub crf: Option<u8>,
+
+    /// Encoder speed; slower gives smaller files [default: medium]
+    #[arg(long, value_enum)]
+    pub speed: Option<Speed>,
+
+    /// Output container, e.g. mp4 or mkv [default: the input's; required for `-o -`]
+    #[arg(long, value_name = "FORMAT")]
+    pub format: Option<String>,
+
+    #[command(flatten)]
+    pub metadata: MetadataArgs,
+
+    /// Use a saved or built-in preset; explicit flags override it
+    #[arg(long)]
+    pub preset: Option<String>,
 
     /// Overwrite without confirmation
     #[arg(long)]
     pub force: bool,
 }
 
-#[derive(Clone, Copy, ValueEnum)]
+#[derive(Clone, Copy, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Quality {
     Low,
     Medium,
     High,
 }
 
-impl Quality {
-    pub fn crf(self) -> u8 {
-        match self {
-            Quality::Low => 28,
-            Quality::Medium => 23,
-            Quality::High => 18,
+impl From<Quality> for video_express::Quality {
+    fn from(quality: Quality) -> Self {
+        match quality {
+            Quality::Low => video_express::Quality::Low,
+            Quality::Medium => video_express::Quality::Medium,
+            Quality::High => video_express::Quality::High,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Speed {
+    Ultrafast,
+    Superfast,
+    Veryfast,
+    Faster,
+    Fast,
+    Medium,
+    Slow,
+    Slower,
+    Veryslow,
+}
+
+impl From<Speed> for video_express::Speed {
+    fn from(speed: Speed) -> Self {
+        match speed {
+            Speed::Ultrafast => video_express::Speed::Ultrafast,
+            Speed::Superfast => video_express::Speed::Superfast,
+            Speed::Veryfast => video_express::Speed::Veryfast,
+            Speed::Faster => video_express::Speed::Faster,
+            Speed::Fast => video_express::Speed::Fast,
+            Speed::Medium => video_express::Speed::Medium,
+            Speed::Slow => video_express::Speed::Slow,
+            Speed::Slower => video_express::Speed::Slower,
+            Speed::Veryslow => video_express::Speed::Veryslow,
         }
     }
 }
 
-#[derive(Parser)]
+#[derive(Clone, Parser)]
 pub struct ConvertArgs {
     /// Target format (mp4, webm, mov, avi, gif)
     pub format: String,
 
-    /// Input video file
-    pub input: PathBuf,
+    /// Input video files, directories or glob patterns
+    #[arg(required = true, value_name = "INPUT")]
+    pub inputs: Vec<PathBuf>,
 
     /// Output file [default: {input_name}.{format}]
     #[arg(short, long)]
     pub output: Option<PathBuf>,
 
+    /// Constant rate factor for H.264 and VP9 (0-63, lower is better)
+    #[arg(long, value_parser = clap::value_parser!(u8).range(0..=63))]
+    pub crf: Option<u8>,
+
+    /// H.264 encoder speed; slower gives smaller files
+    #[arg(long, value_enum)]
+    pub speed: Option<Speed>,
+
+    #[command(flatten)]
+    pub metadata: MetadataArgs,
+
+    /// Use a saved or built-in preset; explicit flags override it
+    #[arg(long)]
+    pub preset: Option<String>,
+
     /// Overwrite without confirmation
     #[arg(long)]
     pub force: bool,
 }
 
-#[derive(Parser)]
+#[derive(Clone, Copy, Args)]
+pub struct MetadataArgs {
+    /// Copy tags like creation time and location, and the file's timestamps (default)
+    #[arg(long, conflicts_with = "strip_metadata")]
+    pub keep_metadata: bool,
+
+    /// Remove all tags and chapters from the output
+    #[arg(long)]
+    pub strip_metadata: bool,
+}
+
+impl From<MetadataArgs> for video_express::Metadata {
+    fn from(args: MetadataArgs) -> Self {
+        match args.strip_metadata {
+            true => video_express::Metadata::Strip,
+            false => video_express::Metadata::Keep,
+        }
+    }
+}
+
+#[derive(Clone, Parser)]
 pub struct InfoArgs {
-    /// Input video file
-    pub input: PathBuf,
+    /// Input video files, directories or glob patterns
+    #[arg(required = true, value_name = "INPUT")]
+    pub inputs: Vec<PathBuf>,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OnConflict {
+    /// Prompt before overwriting
+    Ask,
+    /// Replace the existing file
+    Overwrite,
+    /// Leave the existing file and skip the input
+    Skip,
+    /// Write to `name (1).ext` instead
+    Rename,
+    /// Stop with an error
+    Fail,
+}
+
+#[derive(Clone, Parser)]
+pub struct WatchArgs {
+    /// Directory to watch
+    pub dir: PathBuf,
+
+    /// Command to run on each new file, without the input (e.g. `gif -w 640`)
+    #[arg(
+        required = true,
+        trailing_var_arg = true,
+        allow_hyphen_values = true,
+        value_name = "COMMAND"
+    )]
+    pub command: Vec<String>,
+
+    /// Move originals here after they were processed successfully
+    #[arg(long, value_name = "DIR")]
+    pub done_dir: Option<PathBuf>,
+
+    /// Move originals here when processing fails
+    #[arg(long, value_name = "DIR")]
+    pub failed_dir: Option<PathBuf>,
+
+    /// Seconds a file's size must stay unchanged before it is processed
+    #[arg(long, default_value_t = 2.0, value_name = "SECS")]
+    pub settle: f64,
+
+    /// Also process files already in the directory at startup
+    #[arg(long)]
+    pub existing: bool,
+}
+
+#[derive(Clone, Parser)]
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    pub action: ConfigAction,
+}
+
+#[derive(Clone, Subcommand)]
+pub enum ConfigAction {
+    /// Print the effective value of a key
+    Get { key: String },
+
+    /// Set a key in the user config
+    Set {
+        key: String,
+        value: String,
+
+        /// Write to ./vx.toml instead of the user config
+        #[arg(long)]
+        project: bool,
+    },
+
+    /// List all keys with their effective values
+    List,
+}
+
+#[derive(Clone, Parser)]
+pub struct PresetArgs {
+    #[command(subcommand)]
+    pub action: PresetAction,
+}
+
+#[derive(Clone, Subcommand)]
+pub enum PresetAction {
+    /// Save a preset for a command (e.g. `vx preset save slack-gif gif -w 640 -f 12 -d 6`)
+    Save {
+        name: String,
+
+        #[arg(value_enum)]
+        command: PresetCommand,
+
+        /// GIF width in pixels
+        #[arg(short, long)]
+        width: Option<u32>,
+
+        /// GIF frames per second
+        #[arg(short, long)]
+        fps: Option<u32>,
+
+        /// GIF duration in seconds
+        #[arg(short, long)]
+        duration: Option<f64>,
+
+        /// Compress quality level
+        #[arg(short, long, value_enum)]
+        quality: Option<Quality>,
+
+        /// Constant rate factor
+        #[arg(long, value_parser = clap::value_parser!(u8).range(0..=63))]
+        crf: Option<u8>,
+
+        /// Encoder speed
+        #[arg(long, value_enum)]
+        speed: Option<Speed>,
+    },
+
+    /// List built-in and saved presets
+    List,
+
+    /// Print the settings of a preset
+    Show { name: String },
+
+    /// Delete a saved preset
+    Delete { name: String },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PresetCommand {
+    Gif,
+    Compress,
+    To,
 }
@@ -1,53 +1,61 @@
-use crate::cli::CompressArgs;
-use crate::error::VxError;
-use crate::ffmpeg::{get_video_duration, FfmpegRunner};
-use crate::utils::{confirm_overwrite, default_output_path};
+use crate::batch::{self, Job};
+use crate::cli::{CompressArgs, GlobalArgs, Quality, Speed};
+use crate::commands::{check_input, check_output, configure, expand_inputs, input_duration};
+use crate::inputs::{Input, VIDEO_EXTENSIONS};
+use crate::naming::{output_path, source, OutputName};
 use anyhow::Result;
+use clap::ValueEnum;
+use video_express::utils::is_url;
+use video_express::CompressOptions;
 
-pub fn execute(args: CompressArgs) -> Result<()> {
-    if !args.input.exists() {
-        return Err(VxError::InputNotFound(args.input).into());
-    }
-
-    let ext = args
-        .input
-        .extension()
-        .map(|e| e.to_string_lossy().to_string())
-        .unwrap_or_else(|| "mp4".to_string());
-
-    let output = args
-        .output
-        .unwrap_or_else(|| default_output_path(&args.input, Some("_compressed"), &ext));
-
-    if !args.force && !confirm_overwrite(&output) {
-        return Err(VxError::Cancelled.into());
-    }
-
-    let duration = get_video_duration(&args.input)?;
-
-    let description = format!(
-        "Compressing {} → {}",
-        args.input.file_name().unwrap_or_default().to_string_lossy(),
-        output.file_name().unwrap_or_default().to_string_lossy()
-    );
-
-    let crf = args.quality.crf().to_string();
-
-    FfmpegRunner::new()?
-        .with_progress(&description, Some(duration))
-        .input(&args.input)
-        .args([
-            "-c:v", "libx264",
-            "-crf", &crf,
-            "-preset", "medium",
-            "-c:a", "aac",
-            "-b:a", "128k",
-            "-movflags", "+faststart",
-        ])
-        .output(&output)
-        .overwrite()
-        .run()?;
-
-    println!("Created: {}", output.display());
-    Ok(())
+pub fn execute(args: CompressArgs, global: &GlobalArgs) -> Result<()> {
+    let inputs = expand_inputs(&args.inputs, &args.output, global)?;
+    batch::run(inputs, global, |input| plan(input, &args, global))
+}
+
+fn plan(item: &Input, args: &CompressArgs, global: &GlobalArgs) -> Result<Job> {
+    let input = item.path.as_path();
+    check_input(input)?;
+
+    // Streams like HLS playlists don't name a container we can write, so fall back to MP4.
+    let ext = match args.format {
+        Some(ref format) => format.to_lowercase(),
+        None => source(input)
+            .extension()
+            .map(|e| e.to_string_lossy().to_string())
+            .filter(|e| !is_url(input) || VIDEO_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+            .unwrap_or_else(|| "mp4".to_string()),
+    };
+
+    let options = CompressOptions {
+        quality: args.quality.map(Into::into).unwrap_or_default(),
+        crf: args.crf,
+        speed: args.speed.map(Into::into).unwrap_or_default(),
+        metadata: args.metadata.into(),
+        format: args.format.clone(),
+    };
+
+    let quality = args.quality.unwrap_or(Quality::Medium);
+    let speed = args.speed.unwrap_or(Speed::Medium);
+    let name = OutputName {
+        suffix: Some("_compressed"),
+        ext: &ext,
+        params: vec![
+            ("quality", value_name(quality)),
+            ("crf", options.crf.unwrap_or(options.quality.crf()).to_string()),
+            ("speed", value_name(speed)),
+        ],
+    };
+    let output = output_path(args.output.clone(), item, name, global)?;
+
+    let output = check_output(input, output, args.force, global)?;
+
+    let duration = input_duration(input)?;
+    let runner = configure(video_express::compress(input, &output, &options)?, global);
+
+    Ok(Job::new("Compressing", input, output, runner, duration).warn_if_larger())
+}
+
+fn value_name(value: impl ValueEnum) -> String {
+    value.to_possible_value().map(|v| v.get_name().to_string()).unwrap_or_default()
 }
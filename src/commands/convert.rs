@@ -1,62 +1,48 @@
-use crate::cli::ConvertArgs;
-use crate::error::{VxError, SUPPORTED_FORMATS};
-use crate::ffmpeg::{get_video_duration, FfmpegRunner};
-use crate::utils::{confirm_overwrite, default_output_path};
+use crate::batch::{self, Job};
+use crate::cli::{ConvertArgs, GlobalArgs};
+use crate::commands::{check_input, check_output, configure, expand_inputs, input_duration};
+use crate::inputs::Input;
+use crate::naming::{output_path, OutputName};
 use anyhow::Result;
+use clap::ValueEnum;
+use video_express::ConvertOptions;
+
+pub fn execute(args: ConvertArgs, global: &GlobalArgs) -> Result<()> {
+    let options = ConvertOptions {
+        crf: args.crf,
+        speed: args.speed.map(Into::into),
+        metadata: args.metadata.into(),
+        ..ConvertOptions::new(&args.format)?
+    };
+
+    let inputs = expand_inputs(&args.inputs, &args.output, global)?;
+    batch::run(inputs, global, |input| plan(input, &args, &options, global))
+}
 
-pub fn execute(args: ConvertArgs) -> Result<()> {
-    let format = args.format.to_lowercase();
-
-    if !SUPPORTED_FORMATS.contains(&format.as_str()) {
-        return Err(VxError::UnsupportedFormat {
-            format,
-            supported: SUPPORTED_FORMATS.to_vec(),
-        }
-        .into());
-    }
-
-    if !args.input.exists() {
-        return Err(VxError::InputNotFound(args.input).into());
+fn plan(
+    item: &Input,
+    args: &ConvertArgs,
+    options: &ConvertOptions,
+    global: &GlobalArgs,
+) -> Result<Job> {
+    let input = item.path.as_path();
+    check_input(input)?;
+
+    let mut params = vec![("format", options.format.clone())];
+    if let Some(crf) = args.crf {
+        params.push(("crf", crf.to_string()));
     }
-
-    let output = args.output.unwrap_or_else(|| default_output_path(&args.input, None, &format));
-
-    if !args.force && !confirm_overwrite(&output) {
-        return Err(VxError::Cancelled.into());
+    if let Some(speed) = args.speed.and_then(|s| s.to_possible_value()) {
+        params.push(("speed", speed.get_name().to_string()));
     }
 
-    let duration = get_video_duration(&args.input)?;
-
-    let description = format!(
-        "Converting {} → {}",
-        args.input.file_name().unwrap_or_default().to_string_lossy(),
-        output.file_name().unwrap_or_default().to_string_lossy()
-    );
+    let name = OutputName { suffix: None, ext: &options.format, params };
+    let output = output_path(args.output.clone(), item, name, global)?;
 
-    let codec_args = get_codec_args(&format);
+    let output = check_output(input, output, args.force, global)?;
 
-    FfmpegRunner::new()?
-        .with_progress(&description, Some(duration))
-        .input(&args.input)
-        .args(codec_args)
-        .output(&output)
-        .overwrite()
-        .run()?;
+    let duration = input_duration(input)?;
+    let runner = configure(video_express::convert(input, &output, options)?, global);
 
-    println!("Created: {}", output.display());
-    Ok(())
-}
-
-fn get_codec_args(format: &str) -> Vec<&'static str> {
-    match format {
-        "mp4" => vec!["-c:v", "libx264", "-c:a", "aac"],
-        "webm" => vec!["-c:v", "libvpx-vp9", "-c:a", "libopus"],
-        "mov" => vec!["-c:v", "libx264", "-c:a", "aac"],
-        "avi" => vec!["-c:v", "mpeg4", "-c:a", "libmp3lame"],
-        "gif" => vec![
-            "-vf",
-            "fps=10,scale=480:-1:flags=lanczos,split[s0][s1];[s0]palettegen[p];[s1][p]paletteuse",
-        ],
-        _ => vec![],
-    }
+    Ok(Job::new("Converting", input, output, runner, duration))
 }
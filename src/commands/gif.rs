@@ -1,64 +1,48 @@
-use crate::cli::GifArgs;
-use crate::error::VxError;
-use crate::ffmpeg::{get_video_duration, FfmpegRunner};
-use crate::utils::{confirm_overwrite, default_output_path, parse_time};
+use crate::batch::{self, Job};
+use crate::cli::{GifArgs, GlobalArgs};
+use crate::commands::{check_input, check_output, configure, expand_inputs, input_duration};
+use crate::inputs::Input;
+use crate::naming::{number, output_path, OutputName};
 use anyhow::Result;
+use video_express::utils::parse_time;
+use video_express::GifOptions;
 
-pub fn execute(args: GifArgs) -> Result<()> {
-    if !args.input.exists() {
-        return Err(VxError::InputNotFound(args.input).into());
-    }
+pub fn execute(args: GifArgs, global: &GlobalArgs) -> Result<()> {
+    let inputs = expand_inputs(&args.inputs, &args.output, global)?;
+    batch::run(inputs, global, |input| plan(input, &args, global))
+}
 
-    let output = args.output.unwrap_or_else(|| default_output_path(&args.input, None, "gif"));
+fn plan(item: &Input, args: &GifArgs, global: &GlobalArgs) -> Result<Job> {
+    let input = item.path.as_path();
+    check_input(input)?;
 
-    if !args.force && !confirm_overwrite(&output) {
-        return Err(VxError::Cancelled.into());
-    }
-
-    let duration = get_video_duration(&args.input)?;
-    let effective_duration = match (args.start.as_ref(), args.duration) {
-        (Some(start), Some(dur)) => {
-            let start_secs = parse_time(start)?;
-            (duration - start_secs).min(dur)
-        }
-        (Some(start), None) => {
-            let start_secs = parse_time(start)?;
-            duration - start_secs
-        }
-        (None, Some(dur)) => dur.min(duration),
-        (None, None) => duration,
+    let defaults = GifOptions::default();
+    let options = GifOptions {
+        width: args.width.unwrap_or(defaults.width),
+        fps: args.fps.unwrap_or(defaults.fps),
+        start: args.start.as_deref().map(parse_time).transpose()?,
+        duration: args.duration,
     };
 
-    let description = format!(
-        "Converting {} → {}",
-        args.input.file_name().unwrap_or_default().to_string_lossy(),
-        output.file_name().unwrap_or_default().to_string_lossy()
-    );
-
-    let filter = format!(
-        "fps={},scale={}:-1:flags=lanczos,split[s0][s1];[s0]palettegen[p];[s1][p]paletteuse",
-        args.fps, args.width
-    );
+    let duration = input_duration(input)?.map(|total| options.effective_duration(total));
 
-    let mut runner = FfmpegRunner::new()?
-        .with_progress(&description, Some(effective_duration))
-        .overwrite();
-
-    if let Some(ref start) = args.start {
-        let start_secs = parse_time(start)?;
-        runner = runner.args(["-ss", &start_secs.to_string()]);
-    }
-
-    runner = runner.input(&args.input);
-
-    if let Some(dur) = args.duration {
-        runner = runner.args(["-t", &dur.to_string()]);
+    let mut name = OutputName {
+        suffix: None,
+        ext: "gif",
+        params: vec![
+            ("width", options.width.to_string()),
+            ("fps", options.fps.to_string()),
+            ("start", number(options.start.unwrap_or(0.0))),
+        ],
+    };
+    if let Some(duration) = duration.or(options.duration) {
+        name.params.push(("duration", number(duration.round())));
     }
+    let output = output_path(args.output.clone(), item, name, global)?;
 
-    runner = runner.args(["-vf", &filter]).output(&output);
+    let output = check_output(input, output, args.force, global)?;
 
-    runner.run()?;
+    let runner = configure(video_express::gif(input, &output, &options)?, global);
 
-    println!("Created: {}", output.display());
-    Ok(())
+    Ok(Job::new("Converting", input, output, runner, duration.or(options.duration)))
 }
@@ -1,16 +1,29 @@
-use crate::cli::InfoArgs;
-use crate::error::VxError;
-use crate::ffmpeg::get_video_info;
-use crate::utils::{format_bitrate, format_duration, format_size};
+use crate::cli::{GlobalArgs, InfoArgs};
+use crate::commands::{check_input, expand_inputs};
 use anyhow::Result;
+use std::path::Path;
+use video_express::utils::{format_bitrate, format_duration, format_size, is_url};
+use video_express::get_video_info;
 
-pub fn execute(args: InfoArgs) -> Result<()> {
-    if !args.input.exists() {
-        return Err(VxError::InputNotFound(args.input).into());
+pub fn execute(args: InfoArgs, global: &GlobalArgs) -> Result<()> {
+    for (i, input) in expand_inputs(&args.inputs, &None, global)?.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        print_info(&input.path)?;
     }
 
-    let info = get_video_info(&args.input)?;
-    let filename = args.input.file_name().unwrap_or_default().to_string_lossy();
+    Ok(())
+}
+
+fn print_info(input: &Path) -> Result<()> {
+    check_input(input)?;
+
+    let info = get_video_info(input)?;
+    let filename = match is_url(input) {
+        true => input.to_string_lossy(),
+        false => input.file_name().unwrap_or_default().to_string_lossy(),
+    };
 
     let codec_display = match &info.audio_codec {
         Some(audio) => format!("{} / {}", info.video_codec.to_uppercase(), audio.to_uppercase()),
@@ -23,7 +36,9 @@ pub fn execute(args: InfoArgs) -> Result<()> {
     println!("Resolution  : {}x{}", info.width, info.height);
     println!("FPS         : {:.0}", info.fps);
     println!("Codec       : {codec_display}");
-    println!("File size   : {}", format_size(info.file_size));
+    if !is_url(input) {
+        println!("File size   : {}", format_size(info.file_size));
+    }
     println!("Bitrate     : {}", format_bitrate(info.bitrate));
 
     Ok(())
@@ -2,3 +2,112 @@ pub mod compress;
 pub mod convert;
 pub mod gif;
 pub mod info;
+pub mod watch;
+
+use crate::cli::{GlobalArgs, OnConflict};
+use crate::inputs::{self, ExpandOptions, Input};
+use crate::prompt::confirm_overwrite;
+use anyhow::Result;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use video_express::utils::{is_stdio, is_url, unique_path};
+use video_express::{get_video_duration, FfmpegRunner, VxError};
+
+pub fn configure(runner: FfmpegRunner, global: &GlobalArgs) -> FfmpegRunner {
+    runner
+        .verbose(global.verbose)
+        .log_file(global.log_file.clone())
+}
+
+pub fn check_input(input: &Path) -> Result<()> {
+    if !is_stdio(input) && !is_url(input) && !input.exists() {
+        return Err(VxError::InputNotFound(input.to_path_buf()).into());
+    }
+    Ok(())
+}
+
+/// Length of the input, or `None` when it has none (live streams) or is stdin, which can't be
+/// probed without consuming it.
+pub fn input_duration(input: &Path) -> Result<Option<f64>> {
+    match is_stdio(input) {
+        true => Ok(None),
+        false => Ok(Some(get_video_duration(input)?).filter(|d| *d > 0.0)),
+    }
+}
+
+/// Applies the conflict policy to `output` and returns the path to actually write, which differs
+/// from `output` only with `--on-conflict rename`.
+pub fn check_output(
+    input: &Path,
+    output: PathBuf,
+    force: bool,
+    global: &GlobalArgs,
+) -> Result<PathBuf> {
+    if is_stdio(&output) {
+        if std::io::stdout().is_terminal() && !global.dry_run {
+            return Err(VxError::StdoutIsTerminal.into());
+        }
+        return Ok(output);
+    }
+
+    let policy = conflict_policy(force, global);
+    let is_input = same_file(input, &output);
+
+    if is_input && policy != OnConflict::Rename {
+        return Err(VxError::OutputIsInput(output).into());
+    }
+    if !output.exists() {
+        return Ok(output);
+    }
+
+    match policy {
+        OnConflict::Overwrite => Ok(output),
+        OnConflict::Skip => Err(VxError::OutputExists(output).into()),
+        OnConflict::Rename => Ok(unique_path(&output)),
+        OnConflict::Fail => Err(VxError::OutputConflict(output).into()),
+        OnConflict::Ask if global.dry_run || confirm_overwrite(&output) => Ok(output),
+        OnConflict::Ask => Err(VxError::Cancelled.into()),
+    }
+}
+
+// --force and --skip-existing are shorthands. We only prompt when someone can answer, so
+// scripts and CI fail fast instead of hanging on stdin.
+fn conflict_policy(force: bool, global: &GlobalArgs) -> OnConflict {
+    if force {
+        return OnConflict::Overwrite;
+    }
+    if global.skip_existing {
+        return OnConflict::Skip;
+    }
+    match global.on_conflict.unwrap_or(OnConflict::Ask) {
+        // A dry run never prompts, so it has nothing to fail fast on.
+        OnConflict::Ask if global.dry_run => OnConflict::Ask,
+        OnConflict::Ask if !std::io::stdin().is_terminal() => OnConflict::Fail,
+        policy => policy,
+    }
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+pub fn expand_inputs(
+    inputs: &[PathBuf],
+    output: &Option<PathBuf>,
+    global: &GlobalArgs,
+) -> Result<Vec<Input>> {
+    let options = ExpandOptions {
+        recursive: global.recursive,
+        extensions: global.ext.clone(),
+        exclude: global.out_dir.clone(),
+    };
+
+    let inputs = inputs::expand(inputs, &options)?;
+    if inputs.len() > 1 && output.is_some() {
+        return Err(VxError::OutputWithMultipleInputs.into());
+    }
+    Ok(inputs)
+}
@@ -0,0 +1,274 @@
+use crate::cli::{Cli, Commands, GlobalArgs, OnConflict, WatchArgs};
+use crate::error_output;
+use crate::inputs::ExpandOptions;
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use std::collections::{HashMap, HashSet};
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use video_express::ffmpeg::cancel;
+use video_express::VxError;
+
+const TICK: Duration = Duration::from_millis(500);
+
+pub fn execute(args: WatchArgs, global: &GlobalArgs) -> Result<()> {
+    if !args.dir.is_dir() {
+        return Err(VxError::InputNotFound(args.dir).into());
+    }
+
+    let template = template(&args, global)?;
+    let filter = ExpandOptions { extensions: template.global.ext.clone(), ..Default::default() };
+    let wanted = |name: &OsStr| {
+        !name.to_string_lossy().starts_with('.') && filter.matches(Path::new(name))
+    };
+
+    // Ctrl-C stops the loop (and any encode in progress) instead of killing the process.
+    let _signals = cancel::guard();
+
+    let mut watcher = Watcher::new(&args.dir)?;
+    let mut pending: HashMap<PathBuf, (u64, Instant)> = HashMap::new();
+    let settle = Duration::from_secs_f64(args.settle.max(0.0));
+
+    if args.existing {
+        for name in watcher.existing().filter(|name| wanted(name)) {
+            pending.insert(args.dir.join(name), (0, Instant::now()));
+        }
+    }
+
+    println!("Watching {} for new files (Ctrl-C to stop)", args.dir.display());
+
+    while !cancel::requested() {
+        for name in watcher.wait(TICK)?.into_iter().filter(|name| wanted(name)) {
+            pending.entry(args.dir.join(name)).or_insert((0, Instant::now()));
+        }
+
+        for path in settled(&mut pending, settle) {
+            process(&path, &template, &args)?;
+        }
+    }
+
+    println!("Stopped watching {}", args.dir.display());
+    Ok(())
+}
+
+// Parses the inner command once up front, so mistakes surface before any file arrives, and
+// resolves presets and config the same way a direct invocation would.
+fn template(args: &WatchArgs, global: &GlobalArgs) -> Result<Cli> {
+    let argv = std::iter::once(OsString::from("vx"))
+        .chain(args.command.iter().map(OsString::from))
+        .chain(std::iter::once(args.dir.clone().into_os_string()));
+    let mut cli = Cli::try_parse_from(argv)?;
+
+    if !matches!(cli.command, Commands::Gif(_) | Commands::Compress(_) | Commands::To(_)) {
+        return Err(VxError::UnsupportedWatchCommand(args.command[0].clone()).into());
+    }
+
+    cli.global.inherit(global);
+    crate::prepare(&mut cli)?;
+
+    // Nobody is around to answer a prompt, so overwrite unless another policy was chosen.
+    if !cli.global.skip_existing && matches!(cli.global.on_conflict, None | Some(OnConflict::Ask)) {
+        cli.global.on_conflict = Some(OnConflict::Overwrite);
+    }
+    cli.global.unattended = true;
+
+    let same_dir = |out: &PathBuf| match (out.canonicalize(), args.dir.canonicalize()) {
+        (Ok(out), Ok(dir)) => out == dir,
+        _ => false,
+    };
+    if cli.global.out_dir.as_ref().is_none_or(same_dir) {
+        return Err(VxError::WatchNeedsOutDir(args.dir.clone()).into());
+    }
+
+    Ok(cli)
+}
+
+// A file is ready once its size has stopped changing for `settle`.
+fn settled(pending: &mut HashMap<PathBuf, (u64, Instant)>, settle: Duration) -> Vec<PathBuf> {
+    let mut ready = Vec::new();
+
+    pending.retain(|path, (size, changed)| {
+        let Some(meta) = std::fs::metadata(path).ok().filter(|m| m.is_file()) else {
+            return false;
+        };
+        if meta.len() != *size {
+            *size = meta.len();
+            *changed = Instant::now();
+            return true;
+        }
+        if changed.elapsed() < settle {
+            return true;
+        }
+        ready.push(path.clone());
+        false
+    });
+
+    ready.sort();
+    ready
+}
+
+fn process(path: &Path, template: &Cli, args: &WatchArgs) -> Result<()> {
+    let mut cli = template.clone();
+    match cli.command {
+        Commands::Gif(ref mut a) => a.inputs = vec![path.to_path_buf()],
+        Commands::Compress(ref mut a) => a.inputs = vec![path.to_path_buf()],
+        Commands::To(ref mut a) => a.inputs = vec![path.to_path_buf()],
+        _ => unreachable!("checked when parsing the watch command"),
+    }
+
+    let result = crate::dispatch(cli);
+    let target = match result {
+        Ok(()) => args.done_dir.as_deref(),
+        Err(ref err) if matches!(err.downcast_ref(), Some(VxError::Cancelled)) => return result,
+        Err(ref err) => {
+            error_output::print(err, Some(path), template.global.error_format);
+            args.failed_dir.as_deref()
+        }
+    };
+
+    if let Some(dir) = target
+        && let Err(err) = move_into(path, dir)
+    {
+        let err = anyhow!("could not move to {}: {err}", dir.display());
+        error_output::print(&err, Some(path), template.global.error_format);
+    }
+
+    Ok(())
+}
+
+fn move_into(path: &Path, dir: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let target = dir.join(path.file_name().unwrap_or_default());
+
+    // rename fails across filesystems; fall back to copy + delete.
+    if std::fs::rename(path, &target).is_err() {
+        std::fs::copy(path, &target)?;
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+struct Watcher {
+    dir: PathBuf,
+    #[cfg(target_os = "linux")]
+    inotify: Option<inotify::Inotify>,
+    known: HashSet<OsString>,
+}
+
+impl Watcher {
+    fn new(dir: &Path) -> Result<Self> {
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            #[cfg(target_os = "linux")]
+            inotify: inotify::Inotify::new(dir).ok(),
+            known: list(dir)?,
+        })
+    }
+
+    fn existing(&self) -> impl Iterator<Item = &OsString> {
+        self.known.iter()
+    }
+
+    /// Blocks for up to `timeout` and returns the names of files created or written meanwhile.
+    fn wait(&mut self, timeout: Duration) -> Result<Vec<OsString>> {
+        #[cfg(target_os = "linux")]
+        if let Some(ref inotify) = self.inotify {
+            return Ok(inotify.wait(timeout)?);
+        }
+
+        // Polling fallback: report names that were not in the previous listing.
+        std::thread::sleep(timeout);
+        let current = list(&self.dir)?;
+        let new = current.difference(&self.known).cloned().collect();
+        self.known = current;
+        Ok(new)
+    }
+}
+
+fn list(dir: &Path) -> Result<HashSet<OsString>> {
+    let mut names = HashSet::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.path().is_file() {
+            names.insert(entry.file_name());
+        }
+    }
+    Ok(names)
+}
+
+#[cfg(target_os = "linux")]
+mod inotify {
+    use std::ffi::{CString, OsStr, OsString};
+    use std::io;
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+    use std::time::Duration;
+
+    const EVENT_SIZE: usize = std::mem::size_of::<libc::inotify_event>();
+
+    pub struct Inotify {
+        fd: OwnedFd,
+    }
+
+    impl Inotify {
+        pub fn new(dir: &Path) -> io::Result<Self> {
+            // SAFETY: plain syscall; the returned descriptor is owned by `OwnedFd` below.
+            let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            // SAFETY: `fd` is a freshly created descriptor that nothing else owns.
+            let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+            let path = CString::new(dir.as_os_str().as_bytes())?;
+            let mask = libc::IN_CREATE | libc::IN_CLOSE_WRITE | libc::IN_MOVED_TO;
+            // SAFETY: `path` is a valid NUL-terminated string for the duration of the call.
+            if unsafe { libc::inotify_add_watch(fd.as_raw_fd(), path.as_ptr(), mask) } < 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            Ok(Self { fd })
+        }
+
+        pub fn wait(&self, timeout: Duration) -> io::Result<Vec<OsString>> {
+            let fd = self.fd.as_raw_fd();
+            let mut poll = libc::pollfd { fd, events: libc::POLLIN, revents: 0 };
+            // SAFETY: `poll` points to exactly one initialised pollfd.
+            let ready = unsafe { libc::poll(&mut poll, 1, timeout.as_millis() as libc::c_int) };
+            if ready < 0 {
+                let err = io::Error::last_os_error();
+                return match err.kind() {
+                    io::ErrorKind::Interrupted => Ok(Vec::new()),
+                    _ => Err(err),
+                };
+            }
+
+            let mut names = Vec::new();
+            let mut buf = [0u8; 4096];
+            loop {
+                // SAFETY: the buffer is valid for `buf.len()` bytes of writes.
+                let len = unsafe { libc::read(fd, buf.as_mut_ptr().cast(), buf.len()) };
+                if len <= 0 {
+                    break;
+                }
+
+                let mut offset = 0;
+                while offset + EVENT_SIZE <= len as usize {
+                    // SAFETY: the kernel writes whole events, and the bounds were checked above.
+                    let event: libc::inotify_event =
+                        unsafe { std::ptr::read_unaligned(buf.as_ptr().add(offset).cast()) };
+                    let name = &buf[offset + EVENT_SIZE..offset + EVENT_SIZE + event.len as usize];
+                    let name = name.split(|b| *b == 0).next().unwrap_or_default();
+                    if !name.is_empty() {
+                        names.push(OsStr::from_bytes(name).to_os_string());
+                    }
+                    offset += EVENT_SIZE + event.len as usize;
+                }
+            }
+
+            Ok(names)
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use crate::cli::{Cli, Commands, ConfigAction, ConfigArgs, OnConflict, Quality};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+use video_express::VxError;
+
+pub const KEYS: &[&str] = &[
+    "ffmpeg_path",
+    "output_dir",
+    "on_conflict",
+    "gif.width",
+    "gif.fps",
+    "compress.quality",
+];
+
+#[derive(Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub ffmpeg_path: Option<PathBuf>,
+    pub output_dir: Option<PathBuf>,
+    pub on_conflict: Option<OnConflict>,
+    #[serde(default)]
+    pub gif: GifDefaults,
+    #[serde(default)]
+    pub compress: CompressDefaults,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct GifDefaults {
+    pub width: Option<u32>,
+    pub fps: Option<u32>,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct CompressDefaults {
+    pub quality: Option<Quality>,
+}
+
+impl Config {
+    pub fn user_path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os("VX_CONFIG").filter(|p| !p.is_empty()) {
+            return Some(PathBuf::from(path));
+        }
+
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|p| !p.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+
+        Some(base.join("vx").join("config.toml"))
+    }
+
+    pub fn project_path() -> PathBuf {
+        PathBuf::from("vx.toml")
+    }
+
+    // Project settings win over user settings; CLI flags are applied on top in `apply`.
+    pub fn load() -> Result<Self> {
+        let mut merged = Table::new();
+
+        for path in [Self::user_path(), Some(Self::project_path())].into_iter().flatten() {
+            let table = read_table(&path)?;
+            validate(&table, &path)?;
+            merge(&mut merged, table);
+        }
+
+        Ok(Config::deserialize(merged)?)
+    }
+
+    pub fn apply(&self, cli: &mut Cli) {
+        let global = &mut cli.global;
+        if global.ffmpeg_path.is_none() && std::env::var_os("VX_FFMPEG").is_none() {
+            global.ffmpeg_path = self.ffmpeg_path.clone();
+        }
+        if global.out_dir.is_none() {
+            global.out_dir = self.output_dir.clone();
+        }
+        if global.on_conflict.is_none() && !global.skip_existing {
+            global.on_conflict = self.on_conflict;
+        }
+
+        match &mut cli.command {
+            Commands::Gif(args) => {
+                args.width = args.width.or(self.gif.width);
+                args.fps = args.fps.or(self.gif.fps);
+            }
+            Commands::Compress(args) => args.quality = args.quality.or(self.compress.quality),
+            Commands::To(_)
+            | Commands::Info(_)
+            | Commands::Config(_)
+            | Commands::Preset(_)
+            | Commands::Watch(_) => {}
+        }
+    }
+}
+
+pub fn execute(args: ConfigArgs) -> Result<()> {
+    match args.action {
+        ConfigAction::Get { key } => {
+            check_key(&key)?;
+            match lookup_layers(&key)? {
+                Some((value, _)) => println!("{}", display_value(&value)),
+                None => println!("(not set)"),
+            }
+        }
+        ConfigAction::Set { key, value, project } => {
+            check_key(&key)?;
+            let path = match project {
+                true => Config::project_path(),
+                false => Config::user_path().ok_or_else(|| VxError::InvalidConfig {
+                    path: PathBuf::from("~/.config/vx/config.toml"),
+                    message: "cannot determine the home directory".to_string(),
+                })?,
+            };
+
+            let mut table = read_table(&path)?;
+            insert(&mut table, &key, parse_value(&value));
+
+            validate(&table, &path)?;
+
+            if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&path, toml::to_string(&table)?)?;
+            println!("Set {key} = {value} in {}", path.display());
+        }
+        ConfigAction::List => {
+            for key in KEYS {
+                match lookup_layers(key)? {
+                    Some((value, source)) => {
+                        println!("{key} = {}  ({})", display_value(&value), source.display())
+                    }
+                    None => println!("{key} = (not set)"),
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn check_key(key: &str) -> Result<(), VxError> {
+    if KEYS.contains(&key) {
+        return Ok(());
+    }
+    Err(VxError::UnknownConfigKey { key: key.to_string(), known: KEYS.to_vec() })
+}
+
+fn lookup_layers(key: &str) -> Result<Option<(Value, PathBuf)>> {
+    let layers = [Some(Config::project_path()), Config::user_path()];
+
+    for path in layers.into_iter().flatten() {
+        if let Some(value) = lookup(&read_table(&path)?, key) {
+            return Ok(Some((value.clone(), path)));
+        }
+    }
+
+    Ok(None)
+}
+
+fn validate(table: &Table, path: &Path) -> Result<(), VxError> {
+    Config::deserialize(table.clone()).map(|_| ()).map_err(|err| VxError::InvalidConfig {
+        path: path.to_path_buf(),
+        message: err.message().to_string(),
+    })
+}
+
+fn read_table(path: &Path) -> Result<Table> {
+    if !path.exists() {
+        return Ok(Table::new());
+    }
+
+    let content = std::fs::read_to_string(path)?;
+    content.parse::<Table>().map_err(|err| {
+        VxError::InvalidConfig { path: path.to_path_buf(), message: err.message().to_string() }
+            .into()
+    })
+}
+
+fn merge(base: &mut Table, overlay: Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(base)), Value::Table(overlay)) => merge(base, overlay),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+fn lookup<'a>(table: &'a Table, key: &str) -> Option<&'a Value> {
+    match key.split_once('.') {
+        Some((section, rest)) => lookup(table.get(section)?.as_table()?, rest),
+        None => table.get(key),
+    }
+}
+
+fn insert(table: &mut Table, key: &str, value: Value) {
+    match key.split_once('.') {
+        Some((section, rest)) => {
+            let entry = table
+                .entry(section)
+                .or_insert_with(|| Value::Table(Table::new()));
+            if !entry.is_table() {
+                *entry = Value::Table(Table::new());
+            }
+            if let Value::Table(inner) = entry {
+                insert(inner, rest, value);
+            }
+        }
+        None => {
+            table.insert(key.to_string(), value);
+        }
+    }
+}
+
+fn parse_value(value: &str) -> Value {
+    if let Ok(n) = value.parse::<i64>() {
+        return Value::Integer(n);
+    }
+    if let Ok(b) = value.parse::<bool>() {
+        return Value::Boolean(b);
+    }
+    Value::String(value.to_string())
+}
+
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
@@ -0,0 +1,262 @@
+use crate::cli::ProgressMode;
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use serde_json::{json, Value};
+use std::io::IsTerminal;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use video_express::ffmpeg::{Progress, ProgressHandler};
+use video_express::utils::{format_bitrate, format_duration, format_size, is_stdio};
+
+const PLAIN_INTERVAL: Duration = Duration::from_secs(5);
+
+pub struct ProgressDisplay {
+    mode: ProgressMode,
+    description: String,
+    bar: Option<ProgressBar>,
+    started: Instant,
+    last_line: Option<Instant>,
+    batch: Option<(BatchDisplay, String)>,
+    stderr: bool,
+}
+
+impl ProgressDisplay {
+    pub fn new(description: &str, mode: ProgressMode) -> Self {
+        Self {
+            mode,
+            description: description.to_string(),
+            bar: None,
+            started: Instant::now(),
+            last_line: None,
+            batch: None,
+            stderr: false,
+        }
+    }
+
+    /// Sends all output to stderr, for when stdout carries the encoded video.
+    pub fn on_stderr(mut self, enabled: bool) -> Self {
+        self.stderr = enabled;
+        self
+    }
+
+    /// Attaches the job to a batch: its bar joins the shared display and JSON events carry `input`.
+    pub fn in_batch(mut self, batch: &BatchDisplay, input: &Path) -> Self {
+        self.batch = Some((batch.clone(), input.to_string_lossy().into_owned()));
+        self
+    }
+
+    fn emit(&self, mut event: Value) {
+        if let Some((_, ref input)) = self.batch {
+            event["input"] = input.as_str().into();
+        }
+        match self.stderr {
+            true => eprintln!("{event}"),
+            false => println!("{event}"),
+        }
+    }
+
+    fn println(&self, line: &str) {
+        match self.batch {
+            Some((ref batch, _)) => batch.println(line),
+            None if self.stderr => eprintln!("{line}"),
+            None => println!("{line}"),
+        }
+    }
+}
+
+impl ProgressHandler for ProgressDisplay {
+    fn start(&mut self, total_duration: Option<f64>) {
+        self.started = Instant::now();
+        let terminal = match self.stderr {
+            true => std::io::stderr().is_terminal(),
+            false => std::io::stdout().is_terminal(),
+        };
+        if matches!(self.mode, ProgressMode::Auto) && terminal {
+            let bar = progress_bar(&self.description, total_duration);
+            self.bar = Some(match self.batch {
+                Some((ref batch, _)) => batch.add(bar),
+                None => bar,
+            });
+        }
+    }
+
+    fn update(&mut self, progress: &Progress) {
+        match self.mode {
+            ProgressMode::Auto => {
+                if let Some(ref bar) = self.bar {
+                    if progress.total_duration.is_some() {
+                        bar.set_position((progress.time * 1000.0) as u64);
+                    }
+                    bar.set_prefix(stats(progress));
+                }
+            }
+            ProgressMode::Json => {
+                let event = json!({
+                    "event": "progress",
+                    "percent": progress.percent(),
+                    "out_time": progress.time,
+                    "fps": progress.fps,
+                    "speed": progress.speed,
+                    "bitrate": progress.bitrate,
+                    "size": progress.size,
+                    "eta": progress.eta(),
+                });
+                self.emit(event);
+            }
+            ProgressMode::Plain => {
+                let now = Instant::now();
+                if self.last_line.is_some_and(|last| now - last < PLAIN_INTERVAL) {
+                    return;
+                }
+                self.last_line = Some(now);
+
+                let position = match (progress.percent(), progress.total_duration) {
+                    (Some(percent), Some(total)) => format!(
+                        "{percent:.0}% ({} / {})",
+                        format_duration(progress.time),
+                        format_duration(total)
+                    ),
+                    _ => format_duration(progress.time),
+                };
+                let eta = progress
+                    .eta()
+                    .map(|e| format!(", {} remaining", format_duration(e)))
+                    .unwrap_or_default();
+                self.println(&format!(
+                    "{}: {position}, {:.1}x, {}{eta}",
+                    self.description,
+                    progress.speed,
+                    format_size(progress.size)
+                ));
+            }
+        }
+    }
+
+    fn finish(&mut self) {
+        if let Some(ref bar) = self.bar {
+            bar.finish_and_clear();
+            if let Some((ref batch, _)) = self.batch {
+                batch.multi.remove(bar);
+            }
+        }
+    }
+
+    // Hides the bars while printing, so ffmpeg's log doesn't get drawn over.
+    fn log(&mut self, line: &str) {
+        match (&self.bar, &self.batch) {
+            (Some(_), Some((batch, _))) => batch.multi.suspend(|| eprintln!("{line}")),
+            (Some(bar), None) => bar.suspend(|| eprintln!("{line}")),
+            (None, _) => eprintln!("{line}"),
+        }
+    }
+
+    fn complete(&mut self, output: &Path) {
+        match self.mode {
+            ProgressMode::Json => {
+                let size = std::fs::metadata(output).map(|m| m.len()).unwrap_or(0);
+                let event = json!({
+                    "event": "result",
+                    "output": output.to_string_lossy(),
+                    "size": size,
+                    "elapsed": self.started.elapsed().as_secs_f64(),
+                });
+                self.emit(event);
+            }
+            ProgressMode::Auto | ProgressMode::Plain if is_stdio(output) => {}
+            ProgressMode::Auto | ProgressMode::Plain => {
+                self.println(&format!("Created: {}", output.display()));
+            }
+        }
+    }
+}
+
+/// Shared display for a batch: one bar per running job above an overall bar.
+#[derive(Clone)]
+pub struct BatchDisplay {
+    multi: MultiProgress,
+    overall: ProgressBar,
+    interactive: bool,
+}
+
+impl BatchDisplay {
+    pub fn new(total: usize, mode: ProgressMode) -> Self {
+        let interactive = matches!(mode, ProgressMode::Auto) && std::io::stdout().is_terminal();
+        let multi = match interactive {
+            true => MultiProgress::new(),
+            false => MultiProgress::with_draw_target(ProgressDrawTarget::hidden()),
+        };
+
+        let overall = multi.add(ProgressBar::new(total as u64));
+        overall.set_style(
+            ProgressStyle::default_bar()
+                .template("Overall [{bar:40.green/white}] {pos}/{len} files | {elapsed_precise}")
+                .expect("valid template")
+                .progress_chars("█▓░"),
+        );
+
+        Self { multi, overall, interactive }
+    }
+
+    fn add(&self, bar: ProgressBar) -> ProgressBar {
+        self.multi.insert_before(&self.overall, bar)
+    }
+
+    pub fn println(&self, line: &str) {
+        match self.interactive {
+            true => {
+                self.multi.println(line).ok();
+            }
+            false => println!("{line}"),
+        }
+    }
+
+    pub fn job_done(&self) {
+        self.overall.inc(1);
+    }
+
+    pub fn finish(&self) {
+        self.overall.finish_and_clear();
+    }
+}
+
+fn stats(progress: &Progress) -> String {
+    let mut parts = vec![
+        format!("{:.1}x", progress.speed),
+        format!("{:.0} fps", progress.fps),
+        format_size(progress.size),
+        format_bitrate(progress.bitrate),
+    ];
+
+    if let Some(projected) = progress.projected_size() {
+        parts.push(format!("~{} final", format_size(projected)));
+    }
+
+    parts.join(" · ")
+}
+
+fn progress_bar(description: &str, total_duration: Option<f64>) -> ProgressBar {
+    match total_duration {
+        Some(duration) => {
+            let total = (duration * 1000.0) as u64;
+            let pb = ProgressBar::new(total);
+            pb.set_style(
+                ProgressStyle::default_bar()
+                    .template("{msg}\n[{bar:40.cyan/blue}] {percent}% | {eta} remaining\n{prefix}")
+                    .expect("valid template")
+                    .progress_chars("█▓░"),
+            );
+            pb.set_message(description.to_string());
+            pb
+        }
+        None => {
+            let pb = ProgressBar::new_spinner();
+            pb.set_style(
+                ProgressStyle::default_spinner()
+                    .template("{msg} {spinner} {prefix}")
+                    .expect("valid template"),
+            );
+            pb.set_message(description.to_string());
+            pb.enable_steady_tick(Duration::from_millis(100));
+            pb
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use std::fmt;
 use std::path::PathBuf;
 use thiserror::Error;
 
@@ -6,20 +7,282 @@ pub enum VxError {
     #[error("Input file not found: {0}")]
     InputNotFound(PathBuf),
 
-    #[error("Output format not supported: {format}\n  Supported formats: {}", .supported.join(", "))]
+    #[error("Output format not supported: {format}")]
     UnsupportedFormat { format: String, supported: Vec<&'static str> },
 
-    #[error("Invalid time format: \"{0}\"\n  Expected format: seconds (30) or mm:ss (1:30)")]
+    #[error("Invalid time format: \"{0}\"")]
     InvalidTime(String),
 
-    #[error("ffmpeg not found\n\nInstall ffmpeg first:\n  macOS   : brew install ffmpeg\n  Ubuntu  : sudo apt install ffmpeg\n  Windows : winget install ffmpeg")]
+    #[error("ffmpeg not found")]
     FfmpegNotFound,
 
-    #[error("ffmpeg error: {0}")]
-    FfmpegError(String),
+    #[error("ffmpeg not found at {0}")]
+    FfmpegPathInvalid(PathBuf),
+
+    #[error("ffmpeg {found} is too old")]
+    FfmpegTooOld { found: String, required: String },
+
+    #[error("ffmpeg error: {message}")]
+    FfmpegError { message: String, log: FfmpegLog },
+
+    #[error("Encoder not available: {encoder}")]
+    EncoderNotFound { encoder: String, log: FfmpegLog },
+
+    #[error("Filter not available: {0}")]
+    FilterNotFound(String),
+
+    #[error("Input file is corrupt or not a supported media file")]
+    InvalidInput { log: FfmpegLog },
+
+    #[error("Permission denied")]
+    PermissionDenied { log: FfmpegLog },
+
+    #[error("No space left on device")]
+    NoSpaceLeft { log: FfmpegLog },
+
+    #[error("Codec not supported by the output container")]
+    UnsupportedCodec { log: FfmpegLog },
+
+    #[error("Video width and height must be even for H.264")]
+    OddDimensions { log: FfmpegLog },
+
+    #[error("Invalid config {}: {message}", .path.display())]
+    InvalidConfig { path: PathBuf, message: String },
+
+    #[error("Unknown config key: {key}")]
+    UnknownConfigKey { key: String, known: Vec<&'static str> },
+
+    #[error("Unknown preset: {name}")]
+    PresetNotFound { name: String, available: Vec<String> },
+
+    #[error("Preset '{name}' is for `vx {expected}`, not `vx {command}`")]
+    PresetMismatch { name: String, expected: String, command: String },
+
+    #[error("Invalid preset '{name}': {message}")]
+    InvalidPreset { name: String, message: String },
+
+    #[error("No video files found in {0}")]
+    NoVideoFiles(PathBuf),
+
+    #[error("Invalid output template \"{template}\": {message}")]
+    InvalidTemplate { template: String, message: String },
+
+    #[error("Skipped, output already exists: {0}")]
+    OutputExists(PathBuf),
+
+    #[error("Output already exists: {0}")]
+    OutputConflict(PathBuf),
+
+    #[error("Output is the input file itself: {0}")]
+    OutputIsInput(PathBuf),
+
+    #[error("Another input already writes {0}")]
+    DuplicateOutput(PathBuf),
+
+    #[error("vx watch can run gif, compress or to, not `{0}`")]
+    UnsupportedWatchCommand(String),
+
+    #[error("vx watch needs --out-dir outside {0} so outputs are not picked up as new files")]
+    WatchNeedsOutDir(PathBuf),
+
+    #[error("--output can only be used with a single input (use --out-dir for several)")]
+    OutputWithMultipleInputs,
+
+    #[error("Writing to stdout needs an explicit format (e.g. --format mp4)")]
+    StdoutNeedsFormat,
+
+    #[error("Refusing to write video to a terminal; redirect or pipe stdout")]
+    StdoutIsTerminal,
+
+    #[error("Cannot probe stdin; it can only be read once")]
+    ProbeStdin,
+
+    #[error("{failed} of {total} jobs failed")]
+    BatchFailed { failed: usize, total: usize },
 
     #[error("Operation cancelled")]
     Cancelled,
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Error classes scripts can tell apart by exit code.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ErrorKind {
+    Other,
+    Usage,
+    InputNotFound,
+    UnsupportedFormat,
+    FfmpegMissing,
+    FfmpegFailed,
+    BatchFailed,
+    Cancelled,
+}
+
+impl ErrorKind {
+    pub fn exit_code(self) -> u8 {
+        match self {
+            ErrorKind::Other => 1,
+            ErrorKind::Usage => 2,
+            ErrorKind::InputNotFound => 3,
+            ErrorKind::UnsupportedFormat => 4,
+            ErrorKind::FfmpegMissing => 5,
+            ErrorKind::FfmpegFailed => 6,
+            ErrorKind::BatchFailed => 7,
+            ErrorKind::Cancelled => 130,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorKind::Other => "error",
+            ErrorKind::Usage => "usage",
+            ErrorKind::InputNotFound => "input_not_found",
+            ErrorKind::UnsupportedFormat => "unsupported_format",
+            ErrorKind::FfmpegMissing => "ffmpeg_missing",
+            ErrorKind::FfmpegFailed => "ffmpeg_failed",
+            ErrorKind::BatchFailed => "batch_failed",
+            ErrorKind::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl VxError {
+    // No catch-all arm, so every new variant has to pick its exit code.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            VxError::InputNotFound(_) | VxError::NoVideoFiles(_) => ErrorKind::InputNotFound,
+            VxError::UnsupportedFormat { .. } | VxError::UnsupportedCodec { .. } => {
+                ErrorKind::UnsupportedFormat
+            }
+            VxError::FfmpegNotFound
+            | VxError::FfmpegPathInvalid(_)
+            | VxError::FfmpegTooOld { .. }
+            | VxError::EncoderNotFound { .. }
+            | VxError::FilterNotFound(_) => ErrorKind::FfmpegMissing,
+            VxError::FfmpegError { .. }
+            | VxError::InvalidInput { .. }
+            | VxError::PermissionDenied { .. }
+            | VxError::NoSpaceLeft { .. }
+            | VxError::OddDimensions { .. } => ErrorKind::FfmpegFailed,
+            VxError::InvalidTime(_)
+            | VxError::InvalidConfig { .. }
+            | VxError::UnknownConfigKey { .. }
+            | VxError::PresetNotFound { .. }
+            | VxError::PresetMismatch { .. }
+            | VxError::InvalidPreset { .. }
+            | VxError::InvalidTemplate { .. }
+            | VxError::UnsupportedWatchCommand(_)
+            | VxError::WatchNeedsOutDir(_)
+            | VxError::OutputWithMultipleInputs
+            | VxError::StdoutNeedsFormat
+            | VxError::StdoutIsTerminal
+            | VxError::ProbeStdin => ErrorKind::Usage,
+            VxError::OutputExists(_)
+            | VxError::OutputConflict(_)
+            | VxError::OutputIsInput(_)
+            | VxError::DuplicateOutput(_)
+            | VxError::Io(_) => ErrorKind::Other,
+            VxError::BatchFailed { .. } => ErrorKind::BatchFailed,
+            VxError::Cancelled => ErrorKind::Cancelled,
+        }
+    }
+
+    /// What to do about the error, when there is more to say than the message.
+    pub fn hint(&self) -> Option<String> {
+        let full_build =
+            "Your ffmpeg build does not include it. Install a full build (brew install ffmpeg)";
+        let hint = match self {
+            VxError::UnsupportedFormat { supported, .. } => {
+                format!("Supported formats: {}", supported.join(", "))
+            }
+            VxError::InvalidTime(_) => "Expected format: seconds (30) or mm:ss (1:30)".to_string(),
+            VxError::FfmpegNotFound => [
+                "Install ffmpeg first:",
+                "  macOS   : brew install ffmpeg",
+                "  Ubuntu  : sudo apt install ffmpeg",
+                "  Windows : winget install ffmpeg",
+            ]
+            .join("\n"),
+            VxError::FfmpegPathInvalid(_) => {
+                "Check --ffmpeg-path or the VX_FFMPEG environment variable.".to_string()
+            }
+            VxError::FfmpegTooOld { required, .. } => format!(
+                "vx requires ffmpeg {required} or newer. Upgrade ffmpeg or point \
+                 --ffmpeg-path at a newer build."
+            ),
+            VxError::EncoderNotFound { .. } => format!("{full_build} or pick another format."),
+            VxError::FilterNotFound(_) => format!("{full_build}."),
+            VxError::InvalidInput { .. } => {
+                "Check that the file plays and was fully downloaded.".to_string()
+            }
+            VxError::PermissionDenied { .. } => {
+                "Check read access to the input and write access to the output directory."
+                    .to_string()
+            }
+            VxError::NoSpaceLeft { .. } => {
+                "Free up disk space or write the output to another drive with -o.".to_string()
+            }
+            VxError::UnsupportedCodec { .. } => {
+                "Choose a different output format, e.g. mp4 or mkv.".to_string()
+            }
+            VxError::OddDimensions { .. } => {
+                "Pick an even width, or scale with: -vf \"scale=trunc(iw/2)*2:trunc(ih/2)*2\""
+                    .to_string()
+            }
+            VxError::UnknownConfigKey { known, .. } => format!("Known keys: {}", known.join(", ")),
+            VxError::PresetNotFound { available, .. } => {
+                format!("Available presets: {}", available.join(", "))
+            }
+            VxError::OutputConflict(_) => {
+                "Use --force or --on-conflict overwrite|skip|rename".to_string()
+            }
+            VxError::OutputIsInput(_) => "Use -o, --out-dir or --on-conflict rename".to_string(),
+            VxError::DuplicateOutput(_) => {
+                "Use --output-template with {input_ext} or --on-conflict rename".to_string()
+            }
+            _ => return None,
+        };
+        Some(hint)
+    }
+
+    /// The ffmpeg output attached to errors detected from its log.
+    pub fn log(&self) -> Option<&FfmpegLog> {
+        match self {
+            VxError::FfmpegError { log, .. }
+            | VxError::EncoderNotFound { log, .. }
+            | VxError::InvalidInput { log }
+            | VxError::PermissionDenied { log }
+            | VxError::NoSpaceLeft { log }
+            | VxError::UnsupportedCodec { log }
+            | VxError::OddDimensions { log } => Some(log),
+            _ => None,
+        }
+    }
 }
 
 pub const SUPPORTED_FORMATS: &[&str] = &["mp4", "webm", "mov", "avi", "gif"];
+
+#[derive(Debug, Default)]
+pub struct FfmpegLog {
+    pub tail: Vec<String>,
+    pub saved: Option<PathBuf>,
+}
+
+impl fmt::Display for FfmpegLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.tail.is_empty() {
+            write!(f, "\n\nffmpeg output:")?;
+            for line in &self.tail {
+                write!(f, "\n  {}", line.trim_end())?;
+            }
+        }
+
+        if let Some(ref saved) = self.saved {
+            write!(f, "\n\nFull log: {}", saved.display())?;
+        }
+
+        Ok(())
+    }
+}
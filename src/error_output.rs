@@ -0,0 +1,85 @@
+use crate::cli::ErrorFormat;
+use serde_json::{json, Value};
+use std::path::Path;
+use video_express::{ErrorKind, VxError};
+
+/// The class an error exits with. Command-line mistakes caught by clap count as usage errors.
+pub fn kind(err: &anyhow::Error) -> ErrorKind {
+    if let Some(err) = err.downcast_ref::<VxError>() {
+        return err.kind();
+    }
+    match err.is::<clap::Error>() {
+        true => ErrorKind::Usage,
+        false => ErrorKind::Other,
+    }
+}
+
+/// Prints an error on stderr. `input` is the file it belongs to in a batch or watch run.
+pub fn print(err: &anyhow::Error, input: Option<&Path>, format: ErrorFormat) {
+    match format {
+        ErrorFormat::Text => {
+            let input = input.map(|i| format!("{}: ", i.display())).unwrap_or_default();
+            eprintln!("Error: {input}{}", text(err));
+        }
+        ErrorFormat::Json => eprintln!("{}", to_json(err, input)),
+    }
+}
+
+pub fn warn(message: &str, format: ErrorFormat) {
+    match format {
+        ErrorFormat::Text => eprintln!("Warning: {message}"),
+        ErrorFormat::Json => eprintln!("{}", json!({ "kind": "warning", "message": message })),
+    }
+}
+
+// The message, its hint indented below, then ffmpeg's output.
+fn text(err: &anyhow::Error) -> String {
+    let Some(vx) = err.downcast_ref::<VxError>() else {
+        return format!("{err:?}");
+    };
+
+    let mut text = vx.to_string();
+    for line in vx.hint().iter().flat_map(|hint| hint.lines()) {
+        text.push_str("\n  ");
+        text.push_str(line);
+    }
+    if let Some(log) = vx.log() {
+        text.push_str(&log.to_string());
+    }
+    text
+}
+
+fn to_json(err: &anyhow::Error, input: Option<&Path>) -> Value {
+    let kind = kind(err);
+    let vx = err.downcast_ref::<VxError>();
+
+    let (message, hint) = match (vx, err.downcast_ref::<clap::Error>()) {
+        (Some(vx), _) => (vx.to_string(), vx.hint()),
+        (None, Some(clap)) => clap_message(clap),
+        (None, None) => (format!("{err:#}"), None),
+    };
+
+    let mut event = json!({
+        "kind": kind.as_str(),
+        "exit_code": kind.exit_code(),
+        "message": message,
+        "hint": hint,
+    });
+    if let Some(input) = input {
+        event["input"] = input.to_string_lossy().into();
+    }
+    if let Some(log) = vx.and_then(VxError::log) {
+        event["log"] = json!(log.tail);
+        event["log_file"] = json!(log.saved.as_ref().map(|p| p.to_string_lossy()));
+    }
+    event
+}
+
+// clap only hands out rendered text: "error: <message>", then tips and usage.
+fn clap_message(err: &clap::Error) -> (String, Option<String>) {
+    let text = err.to_string();
+    let text = text.trim_start_matches("error: ");
+    let (message, rest) = text.split_once('\n').unwrap_or((text, ""));
+    let lines: Vec<&str> = rest.lines().map(str::trim).filter(|l| !l.is_empty()).collect();
+    (message.trim_end().to_string(), (!lines.is_empty()).then(|| lines.join("\n")))
+}
@@ -0,0 +1,90 @@
+use crate::error::VxError;
+use ffmpeg_sidecar::version::ffmpeg_version_with_path;
+use std::path::PathBuf;
+use std::sync::{OnceLock, RwLock};
+
+pub const MIN_VERSION: (u32, u32) = (4, 3);
+
+static FFMPEG_OVERRIDE: RwLock<Option<PathBuf>> = RwLock::new(None);
+static VERSION: OnceLock<Option<String>> = OnceLock::new();
+
+pub fn set_ffmpeg_path(path: PathBuf) {
+    *FFMPEG_OVERRIDE.write().expect("ffmpeg path lock") = Some(path);
+}
+
+fn custom_ffmpeg_path() -> Option<PathBuf> {
+    let path = FFMPEG_OVERRIDE.read().expect("ffmpeg path lock").clone();
+    path.or_else(|| std::env::var_os("VX_FFMPEG").filter(|p| !p.is_empty()).map(PathBuf::from))
+}
+
+pub fn ffmpeg_path() -> PathBuf {
+    custom_ffmpeg_path().unwrap_or_else(ffmpeg_sidecar::paths::ffmpeg_path)
+}
+
+// A custom ffmpeg usually ships with its ffprobe in the same directory.
+pub fn ffprobe_path() -> PathBuf {
+    if let Some(path) = std::env::var_os("VX_FFPROBE").filter(|p| !p.is_empty()) {
+        return PathBuf::from(path);
+    }
+
+    if let Some(ffmpeg) = custom_ffmpeg_path() {
+        let mut sibling = ffmpeg.with_file_name("ffprobe");
+        if let Some(ext) = ffmpeg.extension() {
+            sibling.set_extension(ext);
+        }
+        if sibling.exists() {
+            return sibling;
+        }
+    }
+
+    ffmpeg_sidecar::ffprobe::ffprobe_path()
+}
+
+pub fn check_ffmpeg() -> Result<(), VxError> {
+    let version = VERSION.get_or_init(|| ffmpeg_version_with_path(ffmpeg_path()).ok());
+
+    let Some(version) = version else {
+        return Err(match custom_ffmpeg_path() {
+            Some(path) => VxError::FfmpegPathInvalid(path),
+            None => VxError::FfmpegNotFound,
+        });
+    };
+
+    // Git builds report e.g. "N-113684-g…" and cannot be compared; assume they are recent.
+    if let Some(found) = parse_version(version)
+        && found < MIN_VERSION
+    {
+        return Err(VxError::FfmpegTooOld {
+            found: version.clone(),
+            required: format!("{}.{}", MIN_VERSION.0, MIN_VERSION.1),
+        });
+    }
+
+    Ok(())
+}
+
+fn parse_version(version: &str) -> Option<(u32, u32)> {
+    let version = version.strip_prefix('n').unwrap_or(version);
+    let mut parts = version.split(|c: char| !c.is_ascii_digit());
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().and_then(|m| m.parse().ok()).unwrap_or(0);
+    Some((major, minor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_release_and_distro_versions() {
+        assert_eq!(parse_version("6.1.1"), Some((6, 1)));
+        assert_eq!(parse_version("n4.3"), Some((4, 3)));
+        assert_eq!(parse_version("4.4.2-0ubuntu0.22.04.1"), Some((4, 4)));
+        assert_eq!(parse_version("7"), Some((7, 0)));
+    }
+
+    #[test]
+    fn git_builds_have_no_comparable_version() {
+        assert_eq!(parse_version("N-113684-gdeadbeef12"), None);
+    }
+}
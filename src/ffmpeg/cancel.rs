@@ -0,0 +1,53 @@
+use signal_hook::consts::{SIGINT, SIGTERM};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+struct Signals {
+    cancelled: Arc<AtomicBool>,
+    // True while no ffmpeg run is in flight, so the signal keeps its default action.
+    idle: Arc<AtomicBool>,
+    running: Mutex<usize>,
+}
+
+static SIGNALS: OnceLock<Signals> = OnceLock::new();
+
+fn signals() -> &'static Signals {
+    SIGNALS.get_or_init(|| {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let idle = Arc::new(AtomicBool::new(true));
+        for signal in [SIGINT, SIGTERM] {
+            signal_hook::flag::register_conditional_default(signal, Arc::clone(&idle)).ok();
+            signal_hook::flag::register(signal, Arc::clone(&cancelled)).ok();
+        }
+        Signals { cancelled, idle, running: Mutex::new(0) }
+    })
+}
+
+/// While alive, Ctrl-C and SIGTERM request cancellation instead of terminating, so runs can stop
+/// ffmpeg and clean up. Signals are left alone unless the host opts in by holding a guard, and
+/// the request is forgotten once the last guard drops.
+pub struct Guard(());
+
+pub fn guard() -> Guard {
+    let signals = signals();
+    let mut running = signals.running.lock().unwrap();
+    *running += 1;
+    signals.idle.store(false, Ordering::SeqCst);
+    Guard(())
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        let signals = signals();
+        let mut running = signals.running.lock().unwrap();
+        *running -= 1;
+        if *running == 0 {
+            signals.idle.store(true, Ordering::SeqCst);
+            signals.cancelled.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+pub fn requested() -> bool {
+    SIGNALS.get().is_some_and(|signals| signals.cancelled.load(Ordering::SeqCst))
+}
@@ -0,0 +1,97 @@
+use crate::error::{FfmpegLog, VxError};
+use crate::ffmpeg::binary::ffmpeg_path;
+use std::collections::HashSet;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+pub const H264_ENCODERS: &[&str] = &["libx264", "libopenh264"];
+pub const VP9_ENCODERS: &[&str] = &["libvpx-vp9", "libvpx"];
+pub const OPUS_ENCODERS: &[&str] = &["libopus", "opus", "libvorbis", "vorbis"];
+pub const MP3_ENCODERS: &[&str] = &["libmp3lame", "libshine"];
+
+struct Capabilities {
+    encoders: HashSet<String>,
+    filters: HashSet<String>,
+}
+
+static CAPABILITIES: OnceLock<Option<Capabilities>> = OnceLock::new();
+
+// `None` when ffmpeg could not be queried; callers then assume everything is available.
+fn capabilities() -> Option<&'static Capabilities> {
+    CAPABILITIES
+        .get_or_init(|| {
+            let encoders = query("-encoders")?
+                .lines()
+                .skip_while(|line| !line.trim_start().starts_with("---"))
+                .skip(1)
+                .filter_map(|line| line.split_whitespace().nth(1).map(str::to_string))
+                .collect();
+
+            let filters = query("-filters")?
+                .lines()
+                .filter_map(|line| {
+                    let fields: Vec<&str> = line.split_whitespace().collect();
+                    match fields.as_slice() {
+                        [_, name, io, ..] if io.contains("->") => Some(name.to_string()),
+                        _ => None,
+                    }
+                })
+                .collect();
+
+            Some(Capabilities { encoders, filters })
+        })
+        .as_ref()
+}
+
+fn query(flag: &str) -> Option<String> {
+    let output = Command::new(ffmpeg_path())
+        .args(["-hide_banner", flag])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Picks the first available encoder. Falling back to a later one adds a note to `warnings`.
+pub fn select_encoder(
+    candidates: &[&'static str],
+    warnings: &mut Vec<String>,
+) -> Result<&'static str, VxError> {
+    let Some(caps) = capabilities() else {
+        return Ok(candidates[0]);
+    };
+
+    let selected = candidates
+        .iter()
+        .find(|name| caps.encoders.contains(**name))
+        .ok_or_else(|| VxError::EncoderNotFound {
+            encoder: candidates[0].to_string(),
+            log: FfmpegLog::default(),
+        })?;
+
+    if *selected != candidates[0] {
+        warnings.push(format!(
+            "{} is not available in this ffmpeg build, using {selected}",
+            candidates[0]
+        ));
+    }
+
+    Ok(selected)
+}
+
+pub fn require_filters<'a>(names: impl IntoIterator<Item = &'a str>) -> Result<(), VxError> {
+    let Some(caps) = capabilities() else {
+        return Ok(());
+    };
+
+    match names.into_iter().find(|name| !caps.filters.contains(*name)) {
+        Some(missing) => Err(VxError::FilterNotFound(missing.to_string())),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,146 @@
+use crate::error::{FfmpegLog, VxError};
+use std::path::PathBuf;
+use std::process::ExitStatus;
+
+const LOG_TAIL: usize = 8;
+
+pub struct LogTail {
+    lines: Vec<String>,
+    last_error: Option<String>,
+}
+
+impl LogTail {
+    pub fn new() -> Self {
+        Self { lines: Vec::new(), last_error: None }
+    }
+
+    pub fn push_error(&mut self, line: String) {
+        self.last_error = Some(line.clone());
+        self.push(line);
+    }
+
+    pub fn push(&mut self, line: String) {
+        if self.lines.len() == LOG_TAIL {
+            self.lines.remove(0);
+        }
+        self.lines.push(line);
+    }
+
+    /// Explains a run that exited with `status`. The log only picks the kind of error; a crash
+    /// or kill that logged nothing is reported by its exit status.
+    pub fn into_error(self, status: ExitStatus, saved: Option<PathBuf>) -> VxError {
+        let message = self.last_error.unwrap_or_else(|| format!("exited with {status}"));
+        classify(FfmpegLog { tail: self.lines, saved }, message)
+    }
+}
+
+fn classify(mut log: FfmpegLog, message: String) -> VxError {
+    let find = |needles: &[&str]| {
+        log.tail
+            .iter()
+            .rev()
+            .find(|line| needles.iter().any(|n| line.contains(n)))
+            .cloned()
+    };
+
+    if let Some(line) = find(&["Unknown encoder", "Encoder not found", "encoder not found"]) {
+        let encoder = quoted(&line).unwrap_or_else(|| "unknown".to_string());
+        return VxError::EncoderNotFound { encoder, log };
+    }
+
+    if find(&["not divisible by 2"]).is_some() {
+        return VxError::OddDimensions { log };
+    }
+
+    if find(&["Could not find tag for codec", "not currently supported in container"]).is_some() {
+        return VxError::UnsupportedCodec { log };
+    }
+
+    if find(&["No space left on device"]).is_some() {
+        return VxError::NoSpaceLeft { log };
+    }
+
+    if find(&["Permission denied"]).is_some() {
+        return VxError::PermissionDenied { log };
+    }
+
+    if find(&["Invalid data found when processing input", "moov atom not found"]).is_some() {
+        return VxError::InvalidInput { log };
+    }
+
+    if log.tail.last() == Some(&message) {
+        log.tail.pop();
+    }
+    VxError::FfmpegError { message, log }
+}
+
+fn quoted(line: &str) -> Option<String> {
+    let start = line.find('\'')? + 1;
+    let end = start + line[start..].find('\'')?;
+    Some(line[start..end].to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify_log(lines: &[&str]) -> VxError {
+        let tail = lines.iter().map(|line| line.to_string()).collect();
+        let message = lines.last().unwrap().to_string();
+        classify(FfmpegLog { tail, saved: None }, message)
+    }
+
+    #[test]
+    fn names_the_missing_encoder() {
+        let err = classify_log(&[
+            "[vost#0:0 @ 0x5583c8f0] Unknown encoder 'libx265'",
+            "[vost#0:0 @ 0x5583c8f0] Error selecting an encoder",
+        ]);
+        let VxError::EncoderNotFound { encoder, .. } = err else {
+            panic!("expected a missing encoder, got {err:?}");
+        };
+        assert_eq!(encoder, "libx265");
+    }
+
+    #[test]
+    fn recognizes_common_failures() {
+        let odd = "[libx264 @ 0x55d1a2c0] width not divisible by 2 (641x360)";
+        assert!(matches!(classify_log(&[odd]), VxError::OddDimensions { .. }));
+
+        let tag = "[mp4 @ 0x5616f6c0] Could not find tag for codec pcm_s16le in stream #1, codec \
+                   not currently supported in container";
+        assert!(matches!(classify_log(&[tag]), VxError::UnsupportedCodec { .. }));
+
+        let full = "[out#0/mp4 @ 0x55f1] Error writing trailer: No space left on device";
+        assert!(matches!(classify_log(&[full]), VxError::NoSpaceLeft { .. }));
+
+        let denied = "[out#0/gif @ 0x55f1] Error opening output out.gif: Permission denied";
+        assert!(matches!(classify_log(&[denied]), VxError::PermissionDenied { .. }));
+
+        let moov = "[mov,mp4,m4a,3gp,3g2,mj2 @ 0x5581] moov atom not found";
+        let invalid = "[in#0 @ 0x5581] Error opening input: Invalid data found when processing \
+                       input";
+        assert!(matches!(classify_log(&[moov, invalid]), VxError::InvalidInput { .. }));
+    }
+
+    #[test]
+    fn other_errors_keep_ffmpegs_message_out_of_the_tail() {
+        let err = classify_log(&["Input #0, mov,mp4, from 'clip.mp4':", "Conversion failed!"]);
+        let VxError::FfmpegError { message, log } = err else {
+            panic!("expected a generic ffmpeg error, got {err:?}");
+        };
+        assert_eq!(message, "Conversion failed!");
+        assert_eq!(log.tail, ["Input #0, mov,mp4, from 'clip.mp4':"]);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn a_silent_crash_is_reported_by_its_exit_status() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let mut log = LogTail::new();
+        log.push("[info] Input #0, mov,mp4, from 'clip.mp4':".to_string());
+        let err = log.into_error(ExitStatus::from_raw(9), None);
+        assert!(matches!(err, VxError::FfmpegError { ref message, .. } if message.contains("9")));
+    }
+}
@@ -0,0 +1,184 @@
+use std::fmt;
+
+pub struct Filter {
+    name: String,
+    args: Vec<(Option<String>, String)>,
+}
+
+impl Filter {
+    pub fn new(name: &str) -> Self {
+        Self { name: name.to_string(), args: Vec::new() }
+    }
+
+    pub fn arg(mut self, value: impl ToString) -> Self {
+        self.args.push((None, value.to_string()));
+        self
+    }
+
+    pub fn option(mut self, key: &str, value: impl ToString) -> Self {
+        self.args.push((Some(key.to_string()), value.to_string()));
+        self
+    }
+
+    pub fn split(outputs: u32) -> Self {
+        Self::new("split").arg(outputs)
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)?;
+
+        for (i, (key, value)) in self.args.iter().enumerate() {
+            f.write_str(if i == 0 { "=" } else { ":" })?;
+            if let Some(key) = key {
+                write!(f, "{key}=")?;
+            }
+            f.write_str(&escape(value))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct FilterChain {
+    inputs: Vec<String>,
+    filters: Vec<Filter>,
+    outputs: Vec<String>,
+}
+
+impl FilterChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn input(mut self, label: &str) -> Self {
+        self.inputs.push(label.to_string());
+        self
+    }
+
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    pub fn output(mut self, label: &str) -> Self {
+        self.outputs.push(label.to_string());
+        self
+    }
+}
+
+impl fmt::Display for FilterChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for label in &self.inputs {
+            write!(f, "[{label}]")?;
+        }
+
+        for (i, filter) in self.filters.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            write!(f, "{filter}")?;
+        }
+
+        for label in &self.outputs {
+            write!(f, "[{label}]")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct FilterGraph {
+    chains: Vec<FilterChain>,
+}
+
+impl FilterGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn chain(mut self, chain: FilterChain) -> Self {
+        self.chains.push(chain);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chains.is_empty()
+    }
+
+    pub fn filter_names(&self) -> impl Iterator<Item = &str> {
+        self.chains.iter().flat_map(|c| c.filters.iter().map(|f| f.name.as_str()))
+    }
+}
+
+impl fmt::Display for FilterGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, chain) in self.chains.iter().enumerate() {
+            if i > 0 {
+                f.write_str(";")?;
+            }
+            write!(f, "{chain}")?;
+        }
+
+        Ok(())
+    }
+}
+
+// ffmpeg unescapes a value twice: the graph parser first (which splits on [],;), then the
+// option parser (which splits on : and reads a leading key=). So the value is escaped for the
+// option level, and the result escaped again for the graph level.
+fn escape(value: &str) -> String {
+    let option = backslash(value, &['\\', '\'', ':', '=']);
+    backslash(&option, &['\\', '\'', '[', ']', ',', ';'])
+}
+
+fn backslash(value: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if special.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_values_are_untouched() {
+        let scale = Filter::new("scale").arg(480).arg(-1).option("flags", "lanczos");
+        assert_eq!(scale.to_string(), "scale=480:-1:flags=lanczos");
+    }
+
+    #[test]
+    fn escapes_option_then_graph_level() {
+        // The example from ffmpeg's "Notes on filtergraph escaping".
+        let text = "this is a 'string': may contain one, or more, special characters";
+        let drawtext = Filter::new("drawtext").option("text", text);
+        let expected = concat!(
+            r"drawtext=text=this is a \\\'string\\\'\\: ",
+            r"may contain one\, or more\, special characters"
+        );
+        assert_eq!(drawtext.to_string(), expected);
+    }
+
+    #[test]
+    fn escapes_separators_in_paths() {
+        let subtitles = Filter::new("subtitles").arg(r"C:\subs\a=b.srt");
+        assert_eq!(subtitles.to_string(), r"subtitles=C\\:\\\\subs\\\\a\\=b.srt");
+    }
+
+    #[test]
+    fn graph_joins_chains_and_labels() {
+        let graph = FilterGraph::new()
+            .chain(FilterChain::new().filter(Filter::split(2)).output("a").output("b"))
+            .chain(FilterChain::new().input("a").input("b").filter(Filter::new("hstack")));
+        assert_eq!(graph.to_string(), "split=2[a][b];[a][b]hstack");
+    }
+}
@@ -1,4 +1,13 @@
+pub mod binary;
+pub mod cancel;
+pub mod capabilities;
+mod diagnose;
+mod filter;
+mod probe;
 mod progress;
 mod runner;
 
-pub use runner::{get_video_duration, get_video_info, FfmpegRunner};
+pub use filter::{Filter, FilterChain, FilterGraph};
+pub use probe::{get_video_duration, get_video_info, VideoInfo};
+pub use progress::{Progress, ProgressHandler};
+pub use runner::FfmpegRunner;
@@ -0,0 +1,236 @@
+use crate::error::VxError;
+use crate::ffmpeg::binary::{check_ffmpeg, ffmpeg_path, ffprobe_path};
+use crate::ffmpeg::runner::parse_time_to_ms;
+use crate::utils::{is_stdio, is_url};
+use ffmpeg_sidecar::command::FfmpegCommand;
+use ffmpeg_sidecar::event::FfmpegEvent;
+use serde::Deserialize;
+use std::io;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+#[derive(Clone)]
+pub struct VideoInfo {
+    pub duration: f64,
+    pub width: u32,
+    pub height: u32,
+    pub fps: f32,
+    pub video_codec: String,
+    pub audio_codec: Option<String>,
+    pub file_size: u64,
+    pub bitrate: u64,
+}
+
+#[derive(Deserialize)]
+struct ProbeOutput {
+    #[serde(default)]
+    streams: Vec<ProbeStream>,
+    format: Option<ProbeFormat>,
+}
+
+#[derive(Deserialize)]
+struct ProbeStream {
+    codec_type: Option<String>,
+    codec_name: Option<String>,
+    width: Option<u32>,
+    height: Option<u32>,
+    avg_frame_rate: Option<String>,
+    r_frame_rate: Option<String>,
+    duration: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ProbeFormat {
+    duration: Option<String>,
+    bit_rate: Option<String>,
+}
+
+#[derive(Default)]
+struct StreamInfo {
+    duration: Option<f64>,
+    width: u32,
+    height: u32,
+    fps: f32,
+    video_codec: String,
+    audio_codec: Option<String>,
+    bitrate: Option<u64>,
+}
+
+pub fn get_video_duration(path: &Path) -> Result<f64, VxError> {
+    Ok(probe(path)?.duration.unwrap_or(0.0))
+}
+
+pub fn get_video_info(path: &Path) -> Result<VideoInfo, VxError> {
+    let stream = probe(path)?;
+    let file_size = match is_url(path) {
+        true => 0,
+        false => std::fs::metadata(path)?.len(),
+    };
+
+    let duration = stream.duration.unwrap_or(0.0);
+    let bitrate = match stream.bitrate {
+        Some(bitrate) => bitrate,
+        None if duration > 0.0 => ((file_size as f64 * 8.0) / duration) as u64,
+        None => 0,
+    };
+
+    Ok(VideoInfo {
+        duration,
+        width: stream.width,
+        height: stream.height,
+        fps: stream.fps,
+        video_codec: stream.video_codec,
+        audio_codec: stream.audio_codec,
+        file_size,
+        bitrate,
+    })
+}
+
+// Header-only probing first (ffprobe, then `ffmpeg -i` without an output).
+// Decoding the whole file is only used when the container has no usable duration.
+fn probe(path: &Path) -> Result<StreamInfo, VxError> {
+    if is_stdio(path) {
+        return Err(VxError::ProbeStdin);
+    }
+    check_ffmpeg()?;
+
+    let mut info = match probe_ffprobe(path) {
+        Some(info) => info,
+        None => probe_headers(path)?,
+    };
+
+    // Decoding a live stream would never finish, so network inputs stick to the headers.
+    if info.duration.is_none_or(|d| d <= 0.0) && !is_url(path) {
+        info.duration = probe_decode_duration(path)?;
+    }
+
+    Ok(info)
+}
+
+fn probe_ffprobe(path: &Path) -> Option<StreamInfo> {
+    let output = Command::new(ffprobe_path())
+        .args(["-v", "error", "-print_format", "json", "-show_format", "-show_streams"])
+        .arg(path)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let parsed: ProbeOutput = serde_json::from_slice(&output.stdout).ok()?;
+    let mut info = StreamInfo::default();
+
+    for stream in &parsed.streams {
+        match stream.codec_type.as_deref() {
+            Some("video") if info.video_codec.is_empty() => {
+                info.width = stream.width.unwrap_or(0);
+                info.height = stream.height.unwrap_or(0);
+                info.fps = stream
+                    .avg_frame_rate
+                    .as_deref()
+                    .and_then(parse_frame_rate)
+                    .or_else(|| stream.r_frame_rate.as_deref().and_then(parse_frame_rate))
+                    .unwrap_or(0.0);
+                info.video_codec = stream.codec_name.clone().unwrap_or_default();
+            }
+            Some("audio") if info.audio_codec.is_none() => {
+                info.audio_codec = stream.codec_name.clone();
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(format) = parsed.format {
+        info.duration = format.duration.and_then(|d| d.parse().ok());
+        info.bitrate = format.bit_rate.and_then(|b| b.parse().ok());
+    }
+
+    if info.duration.is_none() {
+        info.duration = parsed
+            .streams
+            .iter()
+            .filter_map(|s| s.duration.as_deref()?.parse::<f64>().ok())
+            .reduce(f64::max);
+    }
+
+    Some(info)
+}
+
+fn probe_headers(path: &Path) -> Result<StreamInfo, VxError> {
+    let mut cmd = FfmpegCommand::new_with_path(ffmpeg_path());
+    cmd.input(path);
+
+    let mut child = cmd.spawn()?;
+    let iter = child.iter().map_err(io::Error::other)?;
+
+    let mut info = StreamInfo::default();
+
+    for event in iter {
+        match event {
+            FfmpegEvent::ParsedDuration(d) => {
+                info.duration = Some(d.duration);
+            }
+            FfmpegEvent::ParsedInput(input) if info.duration.is_none() => {
+                info.duration = input.duration;
+            }
+            FfmpegEvent::ParsedInputStream(stream) => {
+                if let Some(video) = stream.video_data() {
+                    if info.video_codec.is_empty() {
+                        info.width = video.width;
+                        info.height = video.height;
+                        info.fps = video.fps;
+                        info.video_codec = stream.format.clone();
+                    }
+                } else if stream.is_audio() && info.audio_codec.is_none() {
+                    info.audio_codec = Some(stream.format.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Reaped so no zombie is left; without an output ffmpeg always exits with an error.
+    child.wait()?;
+    Ok(info)
+}
+
+fn probe_decode_duration(path: &Path) -> Result<Option<f64>, VxError> {
+    let mut cmd = FfmpegCommand::new_with_path(ffmpeg_path());
+    cmd.input(path);
+    cmd.args(["-f", "null", "-"]);
+
+    let mut child = cmd.spawn()?;
+    let iter = child.iter().map_err(io::Error::other)?;
+
+    let mut duration: Option<f64> = None;
+
+    for event in iter {
+        if let FfmpegEvent::Progress(p) = event
+            && let Some(ms) = parse_time_to_ms(&p.time)
+        {
+            duration = Some(ms as f64 / 1000.0);
+        }
+    }
+
+    child.wait()?;
+    Ok(duration)
+}
+
+fn parse_frame_rate(s: &str) -> Option<f32> {
+    let fps = match s.split_once('/') {
+        Some((num, den)) => {
+            let num: f32 = num.parse().ok()?;
+            let den: f32 = den.parse().ok()?;
+            if den == 0.0 {
+                return None;
+            }
+            num / den
+        }
+        None => s.parse().ok()?,
+    };
+
+    (fps > 0.0).then_some(fps)
+}
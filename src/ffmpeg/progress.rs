@@ -1,58 +1,45 @@
-use indicatif::{ProgressBar, ProgressStyle};
-use std::io::IsTerminal;
-use std::time::Duration;
+use std::path::Path;
 
-pub struct ProgressDisplay {
-    bar: Option<ProgressBar>,
-    total_duration: Option<f64>,
+pub struct Progress {
+    pub time: f64,
+    pub total_duration: Option<f64>,
+    pub fps: f32,
+    pub speed: f32,
+    pub size: u64,
+    pub bitrate: u64,
 }
 
-impl ProgressDisplay {
-    pub fn new(description: &str, total_duration: Option<f64>) -> Self {
-        if !std::io::stdout().is_terminal() {
-            return Self { bar: None, total_duration };
-        }
-
-        let bar = match total_duration {
-            Some(duration) => {
-                let total = (duration * 1000.0) as u64;
-                let pb = ProgressBar::new(total);
-                pb.set_style(
-                    ProgressStyle::default_bar()
-                        .template("{msg}\n[{bar:40.cyan/blue}] {percent}% | {eta} remaining")
-                        .expect("valid template")
-                        .progress_chars("█▓░"),
-                );
-                pb.set_message(description.to_string());
-                pb
-            }
-            None => {
-                let pb = ProgressBar::new_spinner();
-                pb.set_style(
-                    ProgressStyle::default_spinner()
-                        .template("{msg} {spinner}")
-                        .expect("valid template"),
-                );
-                pb.set_message(description.to_string());
-                pb.enable_steady_tick(Duration::from_millis(100));
-                pb
-            }
-        };
-
-        Self { bar: Some(bar), total_duration }
+impl Progress {
+    pub fn percent(&self) -> Option<f64> {
+        self.total_duration
+            .filter(|d| *d > 0.0)
+            .map(|d| (self.time / d * 100.0).min(100.0))
     }
 
-    pub fn update(&self, current_time_ms: u64) {
-        if let Some(ref bar) = self.bar {
-            if self.total_duration.is_some() {
-                bar.set_position(current_time_ms);
-            }
-        }
+    pub fn eta(&self) -> Option<f64> {
+        self.total_duration
+            .filter(|_| self.speed > 0.0)
+            .map(|d| ((d - self.time) / self.speed as f64).max(0.0))
     }
 
-    pub fn finish(&self) {
-        if let Some(ref bar) = self.bar {
-            bar.finish_and_clear();
+    pub fn projected_size(&self) -> Option<u64> {
+        let total = self.total_duration?;
+        if self.time <= 0.0 || self.size == 0 {
+            return None;
         }
+        Some((self.size as f64 * total / self.time) as u64)
     }
 }
+
+pub trait ProgressHandler {
+    fn start(&mut self, _total_duration: Option<f64>) {}
+
+    fn update(&mut self, progress: &Progress);
+
+    fn finish(&mut self) {}
+
+    /// ffmpeg's log, line by line, when the runner is verbose.
+    fn log(&mut self, _line: &str) {}
+
+    fn complete(&mut self, _output: &Path) {}
+}
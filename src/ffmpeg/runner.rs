@@ -1,32 +1,63 @@
 use crate::error::VxError;
-use crate::ffmpeg::progress::ProgressDisplay;
-use anyhow::Result;
+use crate::ffmpeg::binary::{check_ffmpeg, ffmpeg_path};
+use crate::ffmpeg::cancel;
+use crate::ffmpeg::diagnose::LogTail;
+use crate::ffmpeg::filter::FilterGraph;
+use crate::ffmpeg::progress::{Progress, ProgressHandler};
+use crate::utils::{is_stdio, is_url, shell_quote};
+use ffmpeg_sidecar::child::FfmpegChild;
 use ffmpeg_sidecar::command::FfmpegCommand;
 use ffmpeg_sidecar::event::{FfmpegEvent, LogLevel};
-use std::path::Path;
+use std::ffi::OsString;
+use std::fs::{FileTimes, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const QUIT_GRACE_PERIOD: Duration = Duration::from_secs(3);
 
 pub struct FfmpegRunner {
     cmd: FfmpegCommand,
-    progress: Option<ProgressDisplay>,
+    progress: Option<Box<dyn ProgressHandler + Send>>,
+    total_duration: Option<f64>,
+    output: Option<PathBuf>,
+    verbose: bool,
+    log_file: Option<PathBuf>,
+    times_from: Option<PathBuf>,
+    stdin: bool,
+    stdout: bool,
+    warnings: Vec<String>,
 }
 
 impl FfmpegRunner {
-    pub fn new() -> Result<Self> {
-        if !ffmpeg_sidecar::command::ffmpeg_is_installed() {
-            return Err(VxError::FfmpegNotFound.into());
-        }
+    pub fn new() -> Result<Self, VxError> {
+        check_ffmpeg()?;
 
-        let cmd = FfmpegCommand::new();
-        Ok(Self { cmd, progress: None })
+        let cmd = FfmpegCommand::new_with_path(ffmpeg_path());
+        Ok(Self {
+            cmd,
+            progress: None,
+            total_duration: None,
+            output: None,
+            verbose: false,
+            log_file: None,
+            times_from: None,
+            stdin: false,
+            stdout: false,
+            warnings: Vec::new(),
+        })
     }
 
     pub fn input(mut self, path: &Path) -> Self {
-        self.cmd.input(path.to_string_lossy());
+        self.stdin |= is_stdio(path);
+        self.cmd.input(path);
         self
     }
 
     pub fn output(mut self, path: &Path) -> Self {
-        self.cmd.output(path.to_string_lossy());
+        self.output = Some(path.to_path_buf());
         self
     }
 
@@ -41,165 +72,318 @@ impl FfmpegRunner {
         self
     }
 
+    pub fn video_filter(self, graph: &FilterGraph) -> Self {
+        if graph.is_empty() {
+            return self;
+        }
+        self.args(["-vf", &graph.to_string()])
+    }
+
     pub fn overwrite(mut self) -> Self {
         self.cmd.overwrite();
         self
     }
 
-    pub fn with_progress(mut self, description: &str, duration: Option<f64>) -> Self {
-        self.progress = Some(ProgressDisplay::new(description, duration));
+    pub fn with_progress(
+        mut self,
+        handler: impl ProgressHandler + Send + 'static,
+        duration: Option<f64>,
+    ) -> Self {
+        self.progress = Some(Box::new(handler));
+        self.total_duration = duration;
+        self
+    }
+
+    /// Passes ffmpeg's log to the progress handler's `log`.
+    pub fn verbose(mut self, enabled: bool) -> Self {
+        self.verbose = enabled;
+        self
+    }
+
+    pub fn log_file(mut self, path: Option<PathBuf>) -> Self {
+        self.log_file = path;
+        self
+    }
+
+    /// Gives the output the modified and accessed times of `source` once it is written.
+    pub fn copy_file_times(mut self, source: &Path) -> Self {
+        if !is_stdio(source) && !is_url(source) {
+            self.times_from = Some(source.to_path_buf());
+        }
         self
     }
 
-    pub fn run(mut self) -> Result<()> {
+    pub(crate) fn warn(mut self, warnings: Vec<String>) -> Self {
+        self.warnings.extend(warnings);
+        self
+    }
+
+    /// Things worth telling the user before running, like a fallback encoder being used.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// The ffmpeg command `run` would execute, quoted for a shell.
+    pub fn command_line(&mut self) -> String {
+        let mut line = self.spawned_command_line();
+        if let Some(ref output) = self.output {
+            line.push(' ');
+            line.push_str(&shell_quote(output.as_os_str()));
+        }
+        line
+    }
+
+    fn spawned_command_line(&mut self) -> String {
+        let inner = self.cmd.as_inner();
+        std::iter::once(inner.get_program())
+            .chain(inner.get_args())
+            .map(shell_quote)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    pub fn run(mut self) -> Result<(), VxError> {
+        let output = self.output.take();
+
+        // ffmpeg writes straight into our stdout; there is no file to stage.
+        if let Some(output) = output.as_deref().filter(|o| is_stdio(o)) {
+            self.cmd.output(output);
+            self.stdout = true;
+
+            let mut progress = self.progress.take();
+            self.execute(progress.as_mut())?;
+            if let Some(ref mut progress) = progress {
+                progress.complete(output);
+            }
+            return Ok(());
+        }
+
+        if let Some(parent) = output.as_deref().and_then(Path::parent)
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        // Encode into a sibling temp file so a failed run never touches an existing output.
+        let staging = output.as_deref().map(staging_path);
+        if let Some(ref staging) = staging {
+            self.cmd.output(staging);
+        }
+
+        // Read before ffmpeg opens the source, which may update its access time.
+        let times = self.times_from.as_deref().map(file_times).transpose()?;
+
+        let mut progress = self.progress.take();
+        let result = self.execute(progress.as_mut());
+
+        if let (Some(staging), Some(output)) = (staging, output) {
+            if result.is_err() {
+                std::fs::remove_file(&staging).ok();
+                return result;
+            }
+
+            std::fs::rename(&staging, &output)?;
+            if let Some(times) = times {
+                std::fs::File::options().write(true).open(&output)?.set_times(times)?;
+            }
+            if let Some(ref mut progress) = progress {
+                progress.complete(&output);
+            }
+        }
+
+        result
+    }
+
+    fn execute(
+        &mut self,
+        mut progress: Option<&mut Box<dyn ProgressHandler + Send>>,
+    ) -> Result<(), VxError> {
+        let command_line = self.spawned_command_line();
+        if self.verbose
+            && let Some(ref mut progress) = progress
+        {
+            progress.log(&format!("$ {command_line}"));
+        }
+
         let mut child = self.cmd.spawn()?;
+        let pump = self.pump_stdio(&mut child);
 
-        let iter = child.iter()?;
-        let mut last_error: Option<String> = None;
+        if let Some(ref mut progress) = progress {
+            progress.start(self.total_duration);
+        }
+
+        let iter = child.iter().map_err(io::Error::other)?;
+        let mut log = LogTail::new();
+        let mut transcript = vec![format!("$ {command_line}")];
 
         for event in iter {
+            if cancel::requested() {
+                break;
+            }
+
+            if let Some(line) = raw_line(&event) {
+                if self.verbose
+                    && let Some(ref mut progress) = progress
+                {
+                    progress.log(line);
+                }
+                transcript.push(line.to_string());
+            }
+
             match event {
                 FfmpegEvent::Progress(p) => {
-                    if let Some(ref progress) = self.progress {
-                        if let Some(ms) = parse_time_to_ms(&p.time) {
-                            progress.update(ms);
-                        }
-                    }
-                }
-                FfmpegEvent::Log(level, msg) => {
-                    if matches!(level, LogLevel::Error | LogLevel::Fatal) {
-                        last_error = Some(msg);
-                    }
-                }
-                FfmpegEvent::Done => {
-                    if let Some(ref progress) = self.progress {
-                        progress.finish();
+                    if let Some(ref mut progress) = progress
+                        && let Some(ms) = parse_time_to_ms(&p.time)
+                    {
+                        progress.update(&Progress {
+                            time: ms as f64 / 1000.0,
+                            total_duration: self.total_duration,
+                            fps: p.fps,
+                            speed: p.speed,
+                            size: p.size_kb as u64 * 1024,
+                            bitrate: (p.bitrate_kbps * 1000.0) as u64,
+                        });
                     }
                 }
+                FfmpegEvent::Log(level, msg) => match level {
+                    LogLevel::Error | LogLevel::Fatal => log.push_error(msg),
+                    LogLevel::Warning => log.push(msg),
+                    _ => {}
+                },
+                FfmpegEvent::Error(msg) => log.push_error(msg),
                 _ => {}
             }
         }
 
-        if let Some(err) = last_error {
-            return Err(VxError::FfmpegError(err).into());
+        if let Some(ref mut progress) = progress {
+            progress.finish();
         }
 
-        Ok(())
-    }
-}
+        if let Some(ref path) = self.log_file {
+            append_log(path, &transcript)?;
+        }
 
-fn parse_time_to_ms(time_str: &str) -> Option<u64> {
-    let parts: Vec<&str> = time_str.split(':').collect();
-    match parts.as_slice() {
-        [hours, mins, secs] => {
-            let hours: f64 = hours.parse().ok()?;
-            let mins: f64 = mins.parse().ok()?;
-            let secs: f64 = secs.parse().ok()?;
-            Some(((hours * 3600.0 + mins * 60.0 + secs) * 1000.0) as u64)
+        if cancel::requested() {
+            stop(&mut child);
+            return Err(VxError::Cancelled);
         }
-        [mins, secs] => {
-            let mins: f64 = mins.parse().ok()?;
-            let secs: f64 = secs.parse().ok()?;
-            Some(((mins * 60.0 + secs) * 1000.0) as u64)
+
+        // Only the exit status decides: ffmpeg logs recoverable decode errors on runs that
+        // succeed, and says nothing when it is killed.
+        let status = child.wait()?;
+        if let Some(pump) = pump {
+            pump.join().expect("stdout pump panicked")?;
         }
-        _ => None,
-    }
-}
 
-pub fn get_video_duration(path: &Path) -> Result<f64> {
-    let mut cmd = FfmpegCommand::new();
-    cmd.input(path.to_string_lossy());
-    cmd.args(["-f", "null", "-"]);
+        if !status.success() {
+            return Err(log.into_error(status, save_failure_log(&transcript)));
+        }
 
-    let mut child = cmd.spawn()?;
-    let iter = child.iter()?;
+        Ok(())
+    }
+}
 
-    let mut duration: Option<f64> = None;
+impl FfmpegRunner {
+    // The child's stdin and stdout are always pipes, so `-` inputs and outputs are streamed
+    // through our own. Returns the stdout copy, which must finish before the run does.
+    fn pump_stdio(&self, child: &mut FfmpegChild) -> Option<JoinHandle<io::Result<()>>> {
+        if self.stdin
+            && let Some(mut stdin) = child.take_stdin()
+        {
+            // Not joined: it may sit in a read on our stdin after ffmpeg has finished.
+            thread::spawn(move || io::copy(&mut io::stdin().lock(), &mut stdin));
+        }
 
-    for event in iter {
-        match event {
-            FfmpegEvent::ParsedDuration(d) => {
-                duration = Some(d.duration);
-            }
-            FfmpegEvent::ParsedInput(input) => {
-                if duration.is_none() {
-                    duration = input.duration;
-                }
-            }
-            _ => {}
+        if !self.stdout {
+            return None;
         }
+        let mut stdout = child.take_stdout()?;
+        Some(thread::spawn(move || {
+            let mut out = io::stdout().lock();
+            io::copy(&mut stdout, &mut out)?;
+            out.flush()
+        }))
     }
+}
 
-    Ok(duration.unwrap_or(0.0))
+fn raw_line(event: &FfmpegEvent) -> Option<&str> {
+    let line = match event {
+        FfmpegEvent::ParsedVersion(v) => &v.raw_log_message,
+        FfmpegEvent::ParsedConfiguration(c) => &c.raw_log_message,
+        FfmpegEvent::ParsedStreamMapping(line) => line,
+        FfmpegEvent::ParsedInput(i) => &i.raw_log_message,
+        FfmpegEvent::ParsedOutput(o) => &o.raw_log_message,
+        FfmpegEvent::ParsedInputStream(s) | FfmpegEvent::ParsedOutputStream(s) => &s.raw_log_message,
+        FfmpegEvent::ParsedDuration(d) => &d.raw_log_message,
+        FfmpegEvent::Log(_, line) | FfmpegEvent::Error(line) => line,
+        FfmpegEvent::Progress(p) => &p.raw_log_message,
+        _ => return None,
+    };
+    Some(line)
 }
 
-pub struct VideoInfo {
-    pub duration: f64,
-    pub width: u32,
-    pub height: u32,
-    pub fps: f32,
-    pub video_codec: String,
-    pub audio_codec: Option<String>,
-    pub file_size: u64,
-    pub bitrate: u64,
+// One write per job, so transcripts of parallel jobs sharing the file don't interleave.
+fn append_log(path: &Path, transcript: &[String]) -> io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all((transcript.join("\n") + "\n\n").as_bytes())
 }
 
-pub fn get_video_info(path: &Path) -> Result<VideoInfo> {
-    let metadata = std::fs::metadata(path)?;
+fn save_failure_log(transcript: &[String]) -> Option<PathBuf> {
+    let stamp = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+    let path = std::env::temp_dir().join(format!("vx-{stamp}-{}.log", std::process::id()));
+    std::fs::write(&path, transcript.join("\n") + "\n").ok()?;
+    Some(path)
+}
 
-    let mut cmd = FfmpegCommand::new();
-    cmd.input(path.to_string_lossy());
-    cmd.args(["-f", "null", "-"]);
+fn file_times(path: &Path) -> io::Result<FileTimes> {
+    let meta = std::fs::metadata(path)?;
+    Ok(FileTimes::new().set_accessed(meta.accessed()?).set_modified(meta.modified()?))
+}
 
-    let mut child = cmd.spawn()?;
-    let iter = child.iter()?;
+// Numbered per run, so two jobs can never share a staging file even when aimed at one output.
+fn staging_path(output: &Path) -> PathBuf {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let n = NEXT.fetch_add(1, Ordering::Relaxed);
+    let mut name = OsString::from(format!(".vx-{}-{n}-", std::process::id()));
+    name.push(output.file_name().unwrap_or_default());
+    output.with_file_name(name)
+}
 
-    let mut duration = 0.0f64;
-    let mut width = 0u32;
-    let mut height = 0u32;
-    let mut fps = 0.0f32;
-    let mut video_codec = String::new();
-    let mut audio_codec: Option<String> = None;
+// Ask ffmpeg to finish via `q` on stdin; kill it if it does not exit in time. When stdin is the
+// input stream there is no way to ask, so it is killed right away.
+fn stop(child: &mut FfmpegChild) {
+    if child.quit().is_err() {
+        child.kill().ok();
+        child.wait().ok();
+        return;
+    }
 
-    for event in iter {
-        match event {
-            FfmpegEvent::ParsedDuration(d) => {
-                duration = d.duration;
-            }
-            FfmpegEvent::ParsedInput(input) => {
-                if duration == 0.0 {
-                    duration = input.duration.unwrap_or(0.0);
-                }
-            }
-            FfmpegEvent::ParsedInputStream(stream) => {
-                if let Some(video) = stream.video_data() {
-                    width = video.width;
-                    height = video.height;
-                    fps = video.fps;
-                    video_codec = stream.format.clone();
-                } else if stream.is_audio() && audio_codec.is_none() {
-                    audio_codec = Some(stream.format.clone());
-                }
-            }
-            _ => {}
+    let deadline = Instant::now() + QUIT_GRACE_PERIOD;
+    while Instant::now() < deadline {
+        if let Ok(Some(_)) = child.as_inner_mut().try_wait() {
+            return;
         }
+        thread::sleep(Duration::from_millis(50));
     }
 
-    let file_size = metadata.len();
-    let bitrate = if duration > 0.0 {
-        ((file_size as f64 * 8.0) / duration) as u64
-    } else {
-        0
-    };
+    child.kill().ok();
+    child.wait().ok();
+}
 
-    Ok(VideoInfo {
-        duration,
-        width,
-        height,
-        fps,
-        video_codec,
-        audio_codec,
-        file_size,
-        bitrate,
-    })
+pub(super) fn parse_time_to_ms(time_str: &str) -> Option<u64> {
+    let parts: Vec<&str> = time_str.split(':').collect();
+    match parts.as_slice() {
+        [hours, mins, secs] => {
+            let hours: f64 = hours.parse().ok()?;
+            let mins: f64 = mins.parse().ok()?;
+            let secs: f64 = secs.parse().ok()?;
+            Some(((hours * 3600.0 + mins * 60.0 + secs) * 1000.0) as u64)
+        }
+        [mins, secs] => {
+            let mins: f64 = mins.parse().ok()?;
+            let secs: f64 = secs.parse().ok()?;
+            Some(((mins * 60.0 + secs) * 1000.0) as u64)
+        }
+        _ => None,
+    }
 }
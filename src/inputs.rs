@@ -0,0 +1,216 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use video_express::utils::is_url;
+use video_express::VxError;
+
+pub const VIDEO_EXTENSIONS: &[&str] = &[
+    "mp4", "m4v", "mov", "mkv", "webm", "avi", "wmv", "flv", "mpg", "mpeg", "ts", "mts", "3gp",
+    "gif",
+];
+
+pub struct Input {
+    pub path: PathBuf,
+    /// Position in the expanded input list, for the `{counter}` template placeholder.
+    pub index: usize,
+    /// Directory of the file relative to the directory argument it was found in, so outputs
+    /// can mirror the input tree. Empty for files named directly.
+    pub relative_dir: PathBuf,
+}
+
+impl Input {
+    fn new(path: PathBuf) -> Self {
+        Self { path, index: 0, relative_dir: PathBuf::new() }
+    }
+}
+
+#[derive(Default)]
+pub struct ExpandOptions {
+    pub recursive: bool,
+    /// Extensions to pick up from directories and patterns; `VIDEO_EXTENSIONS` when empty.
+    pub extensions: Vec<String>,
+    /// Directory to leave out of recursive walks, typically `--out-dir`.
+    pub exclude: Option<PathBuf>,
+}
+
+impl ExpandOptions {
+    pub fn matches(&self, path: &Path) -> bool {
+        let Some(ext) = path.extension().and_then(|ext| ext.to_str()) else {
+            return false;
+        };
+        let ext = ext.to_lowercase();
+
+        match self.extensions.is_empty() {
+            true => VIDEO_EXTENSIONS.contains(&ext.as_str()),
+            false => {
+                self.extensions.iter().any(|e| e.trim_start_matches('.').eq_ignore_ascii_case(&ext))
+            }
+        }
+    }
+
+    fn excluded(&self, dir: &Path) -> bool {
+        let Some(ref exclude) = self.exclude else {
+            return false;
+        };
+        match (dir.canonicalize(), exclude.canonicalize()) {
+            (Ok(dir), Ok(exclude)) => dir == exclude,
+            _ => false,
+        }
+    }
+}
+
+/// Expands directories and glob patterns into a sorted, de-duplicated list of files.
+/// Plain paths and URLs are passed through untouched so missing files are reported per input.
+pub fn expand(inputs: &[PathBuf], options: &ExpandOptions) -> Result<Vec<Input>> {
+    let mut files = Vec::new();
+
+    for input in inputs {
+        if is_url(input) {
+            files.push(Input::new(input.clone()));
+        } else if input.is_dir() {
+            let start = files.len();
+            walk(input, Path::new(""), options, &mut files)?;
+            if files.len() == start {
+                return Err(VxError::NoVideoFiles(input.clone()).into());
+            }
+        } else if !input.exists() && is_pattern(input) {
+            let found = glob(input, options)?;
+            if found.is_empty() {
+                return Err(VxError::InputNotFound(input.clone()).into());
+            }
+            files.extend(found.into_iter().map(Input::new));
+        } else {
+            files.push(Input::new(input.clone()));
+        }
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    files.retain(|file| seen.insert(file.path.clone()));
+    for (index, file) in files.iter_mut().enumerate() {
+        file.index = index;
+    }
+    Ok(files)
+}
+
+fn walk(
+    dir: &Path,
+    relative: &Path,
+    options: &ExpandOptions,
+    files: &mut Vec<Input>,
+) -> Result<()> {
+    let mut entries = std::fs::read_dir(dir)?.collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let name = entry.file_name();
+        // Hidden entries include our own `.vx-*` staging files.
+        if name.to_string_lossy().starts_with('.') {
+            continue;
+        }
+
+        let path = entry.path();
+        if path.is_dir() {
+            if options.recursive && !options.excluded(&path) {
+                walk(&path, &relative.join(&name), options, files)?;
+            }
+        } else if options.matches(&path) {
+            files.push(Input { path, index: 0, relative_dir: relative.to_path_buf() });
+        }
+    }
+
+    Ok(())
+}
+
+fn is_pattern(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.as_encoded_bytes().iter().any(|b| matches!(b, b'*' | b'?')))
+}
+
+// Only the last component may contain wildcards; shells expand everything else before we see it.
+fn glob(pattern: &Path, options: &ExpandOptions) -> Result<Vec<PathBuf>> {
+    // Matched as bytes so file names that aren't valid UTF-8 are still found.
+    let name = pattern.file_name().unwrap_or_default().as_encoded_bytes();
+    let dir = match pattern.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    // An explicit extension in the pattern (`*.txt`) wins over the video filter.
+    let filter_extensions = name.ends_with(b"*") || !options.extensions.is_empty();
+
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        let bytes = file_name.as_encoded_bytes();
+        if !bytes.starts_with(b".")
+            && wildcard_match(name, bytes)
+            && entry.path().is_file()
+            && (!filter_extensions || options.matches(&entry.path()))
+        {
+            files.push(pattern.with_file_name(&file_name));
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+// `?` stands for one character, so names are compared by char when both sides are UTF-8, and
+// by byte only for names that aren't.
+fn wildcard_match(pattern: &[u8], name: &[u8]) -> bool {
+    match (std::str::from_utf8(pattern), std::str::from_utf8(name)) {
+        (Ok(pattern), Ok(name)) => {
+            let pattern: Vec<char> = pattern.chars().collect();
+            let name: Vec<char> = name.chars().collect();
+            match_units(&pattern, &name)
+        }
+        _ => match_units(pattern, name),
+    }
+}
+
+fn match_units<T: Copy + PartialEq + From<u8>>(pattern: &[T], name: &[T]) -> bool {
+    let (star, question) = (T::from(b'*'), T::from(b'?'));
+    match (pattern.first(), name.first()) {
+        (None, None) => true,
+        (Some(&p), _) if p == star => {
+            match_units(&pattern[1..], name)
+                || (!name.is_empty() && match_units(pattern, &name[1..]))
+        }
+        (Some(&p), Some(_)) if p == question => match_units(&pattern[1..], &name[1..]),
+        (Some(p), Some(n)) if p == n => match_units(&pattern[1..], &name[1..]),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(target_os = "linux")]
+    use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+    #[test]
+    fn question_mark_matches_one_character() {
+        assert!(wildcard_match(b"clip?.mp4", "clip\u{e9}.mp4".as_bytes()));
+        assert!(!wildcard_match(b"clip?.mp4", b"clip.mp4"));
+        assert!(wildcard_match(b"clip?.mp4", b"clip\xff.mp4"));
+        assert!(wildcard_match(b"*.mov", b"caf\xe9 clip.mov"));
+    }
+
+    // Linux only: APFS and other filesystems reject file names that aren't valid UTF-8.
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn glob_keeps_non_utf8_names_intact() {
+        let dir = std::env::temp_dir().join(format!("vx-glob-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let name = OsStr::from_bytes(b"caf\xe9 clip.mov");
+        std::fs::write(dir.join(name), b"").unwrap();
+        std::fs::write(dir.join("notes.txt"), b"").unwrap();
+
+        let found = glob(&dir.join("caf*"), &ExpandOptions::default());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(found.unwrap(), vec![dir.join(name)]);
+    }
+}
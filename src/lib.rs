@@ -0,0 +1,13 @@
+pub mod error;
+pub mod ffmpeg;
+pub mod ops;
+pub mod utils;
+
+pub use error::{ErrorKind, VxError};
+pub use ffmpeg::{
+    get_video_duration, get_video_info, FfmpegRunner, Filter, FilterChain, FilterGraph, Progress,
+    ProgressHandler, VideoInfo,
+};
+pub use ops::{
+    compress, convert, gif, CompressOptions, ConvertOptions, GifOptions, Metadata, Quality, Speed,
+};
@@ -1,20 +1,90 @@
+mod batch;
 mod cli;
 mod commands;
-mod error;
-mod ffmpeg;
-mod utils;
+mod config;
+mod display;
+mod error_output;
+mod inputs;
+mod naming;
+mod preset;
+mod prompt;
+mod report;
 
 use anyhow::Result;
 use clap::Parser;
-use cli::{Cli, Commands};
+use cli::{Cli, Commands, ErrorFormat};
+use config::Config;
+use std::process::ExitCode;
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+fn main() -> ExitCode {
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(err) => return usage_error(err),
+    };
 
+    let format = cli.global.error_format;
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => fail(err, format),
+    }
+}
+
+fn run(mut cli: Cli) -> Result<()> {
+    if let Commands::Config(args) = cli.command {
+        return config::execute(args);
+    }
+    if let Commands::Preset(args) = cli.command {
+        return preset::execute(args);
+    }
+
+    prepare(&mut cli)?;
+    dispatch(cli)
+}
+
+// Explicit flags win, then the preset, then config defaults.
+fn prepare(cli: &mut Cli) -> Result<()> {
+    preset::apply(cli)?;
+    Config::load()?.apply(cli);
+
+    if let Some(ref path) = cli.global.ffmpeg_path {
+        video_express::ffmpeg::binary::set_ffmpeg_path(path.clone());
+    }
+
+    Ok(())
+}
+
+fn dispatch(cli: Cli) -> Result<()> {
     match cli.command {
-        Commands::Gif(args) => commands::gif::execute(args),
-        Commands::Compress(args) => commands::compress::execute(args),
-        Commands::To(args) => commands::convert::execute(args),
-        Commands::Info(args) => commands::info::execute(args),
+        Commands::Gif(args) => commands::gif::execute(args, &cli.global),
+        Commands::Compress(args) => commands::compress::execute(args, &cli.global),
+        Commands::To(args) => commands::convert::execute(args, &cli.global),
+        Commands::Info(args) => commands::info::execute(args, &cli.global),
+        Commands::Watch(args) => commands::watch::execute(args, &cli.global),
+        Commands::Config(_) | Commands::Preset(_) => unreachable!("handled before loading config"),
     }
 }
+
+// Parsing failed, so --error-format has to be found in the raw arguments.
+fn usage_error(err: clap::Error) -> ExitCode {
+    let args: Vec<_> = std::env::args_os().collect();
+    let json = args.iter().any(|arg| arg == "--error-format=json")
+        || args.windows(2).any(|pair| pair[0] == "--error-format" && pair[1] == "json");
+    let format = match json {
+        true => ErrorFormat::Json,
+        false => ErrorFormat::Text,
+    };
+    fail(err.into(), format)
+}
+
+fn fail(err: anyhow::Error, format: ErrorFormat) -> ExitCode {
+    // clap renders its own errors in text mode, and help or version output is no error at all.
+    // The inner command of `vx watch` is parsed later, so this covers both.
+    if let Some(err) = err.downcast_ref::<clap::Error>()
+        && (format == ErrorFormat::Text || !err.use_stderr())
+    {
+        err.exit();
+    }
+
+    error_output::print(&err, None, format);
+    ExitCode::from(error_output::kind(&err).exit_code())
+}
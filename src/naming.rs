@@ -0,0 +1,192 @@
+use crate::cli::GlobalArgs;
+use crate::inputs::Input;
+use anyhow::Result;
+use std::borrow::Cow;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use video_express::utils::{default_output_path, is_stdio, is_url, render_template, url_file_name};
+use video_express::{get_video_info, VideoInfo};
+
+/// How a command names its output when no `-o` is given.
+pub struct OutputName<'a> {
+    pub suffix: Option<&'a str>,
+    pub ext: &'a str,
+    /// Operation parameters exposed to `--output-template`. `width`, `fps` and `duration` here
+    /// describe the output and take precedence over the probed input values.
+    pub params: Vec<(&'static str, String)>,
+}
+
+// With --out-dir, the default output keeps its file name and is re-rooted under the out dir,
+// recreating the input's position inside the directory tree it was found in. A template replaces
+// the file name, or the whole path when it starts from {dir}.
+pub fn output_path(
+    output: Option<PathBuf>,
+    input: &Input,
+    name: OutputName,
+    global: &GlobalArgs,
+) -> Result<PathBuf> {
+    if let Some(output) = output {
+        return Ok(output);
+    }
+
+    let default = default_output_path(&source(&input.path), name.suffix, name.ext);
+    let dir = match global.out_dir {
+        Some(ref out_dir) => out_dir.join(&input.relative_dir),
+        None => default.parent().unwrap_or(Path::new("")).to_path_buf(),
+    };
+
+    let Some(ref template) = global.output_template else {
+        return Ok(dir.join(default.file_name().unwrap_or_default()));
+    };
+
+    // A bare file name has an empty parent, which would turn "{dir}/x" into the absolute "/x".
+    let template_dir = match dir.as_os_str().is_empty() {
+        true => Path::new("."),
+        false => dir.as_path(),
+    };
+    let rendered = PathBuf::from(render(template, input, &name, template_dir)?);
+    // Templates without {dir} are relative to where the default output would have gone.
+    match template.contains("{dir}") {
+        true => Ok(rendered),
+        false => Ok(dir.join(rendered)),
+    }
+}
+
+/// The file an input's outputs are named after. Stdin and URLs have no local file, so their
+/// outputs are named as if one called "stdin", or after the URL's last path segment, sat in the
+/// current directory.
+pub fn source(path: &Path) -> Cow<'_, Path> {
+    if is_stdio(path) {
+        return Cow::Borrowed(Path::new("stdin"));
+    }
+    if is_url(path) {
+        let name = path.to_str().and_then(url_file_name).unwrap_or_else(|| "stream".to_string());
+        return Cow::Owned(PathBuf::from(name));
+    }
+    Cow::Borrowed(path)
+}
+
+fn render(template: &str, input: &Input, name: &OutputName, dir: &Path) -> Result<OsString> {
+    let mut info: Option<VideoInfo> = None;
+    let mut probe = |path: &Path| -> Result<VideoInfo> {
+        if info.is_none() {
+            info = Some(get_video_info(path)?);
+        }
+        Ok(info.clone().expect("probed above"))
+    };
+
+    let param = |key: &str| name.params.iter().find(|(k, _)| *k == key).map(|(_, v)| v.clone());
+    let source = source(&input.path);
+
+    render_template(template, |key| {
+        if let Some(value) = param(key) {
+            return Ok(Some(value.into()));
+        }
+
+        // Path pieces are passed through as OsStr to keep non-UTF-8 names intact.
+        let path_piece = match key {
+            "dir" => Some(dir.as_os_str()),
+            "stem" => Some(source.file_stem().unwrap_or_default()),
+            "input_ext" => Some(source.extension().unwrap_or_default()),
+            _ => None,
+        };
+        if let Some(piece) = path_piece {
+            return Ok(Some(piece.to_os_string()));
+        }
+
+        let value = match key {
+            "ext" => name.ext.to_string(),
+            "counter" => (input.index + 1).to_string(),
+            "date" => timestamp().0,
+            "time" => timestamp().1,
+            "width" => probe(&input.path)?.width.to_string(),
+            // Keep the aspect ratio when the operation sets an output width.
+            "height" => {
+                let info = probe(&input.path)?;
+                match param("width").and_then(|w| w.parse::<u64>().ok()) {
+                    Some(width) if info.width > 0 => {
+                        (width * info.height as u64 / info.width as u64).to_string()
+                    }
+                    _ => info.height.to_string(),
+                }
+            }
+            "fps" => number(probe(&input.path)?.fps as f64),
+            "duration" => number(probe(&input.path)?.duration.round()),
+            "codec" => probe(&input.path)?.video_codec,
+            _ => return Ok(None),
+        };
+        Ok(Some(value.into()))
+    })
+}
+
+pub fn number(value: f64) -> String {
+    let formatted = format!("{value:.2}");
+    formatted.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+// UTC date (YYYY-MM-DD) and time (HHMMSS) of the current moment.
+fn timestamp() -> (String, String) {
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    utc(secs)
+}
+
+fn utc(secs: u64) -> (String, String) {
+    let (days, rem) = ((secs / 86_400) as i64, secs % 86_400);
+
+    // Civil-from-days (Howard Hinnant), valid for the whole Unix era.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    (
+        format!("{year:04}-{month:02}-{day:02}"),
+        format!("{:02}{:02}{:02}", rem / 3600, rem % 3600 / 60, rem % 60),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct Args {
+        #[command(flatten)]
+        global: GlobalArgs,
+    }
+
+    fn gif_output(input: &str, args: &[&str]) -> PathBuf {
+        let global = Args::parse_from(std::iter::once("vx").chain(args.iter().copied())).global;
+        let input = Input { path: PathBuf::from(input), index: 0, relative_dir: PathBuf::new() };
+        let name = OutputName { suffix: None, ext: "gif", params: Vec::new() };
+        output_path(None, &input, name, &global).unwrap()
+    }
+
+    #[test]
+    fn dir_of_a_bare_file_name_is_the_current_directory() {
+        let template = ["--output-template", "{dir}/{stem}_x.{ext}"];
+        assert_eq!(gif_output("clip.mp4", &template), Path::new("./clip_x.gif"));
+        assert_eq!(gif_output("-", &template), Path::new("./stdin_x.gif"));
+        assert_eq!(gif_output("in/clip.mp4", &template), Path::new("in/clip_x.gif"));
+        assert_eq!(gif_output("clip.mp4", &[]), Path::new("clip.gif"));
+    }
+
+    #[test]
+    fn formats_known_dates() {
+        let utc = |secs| {
+            let (date, time) = utc(secs);
+            format!("{date} {time}")
+        };
+        assert_eq!(utc(0), "1970-01-01 000000");
+        assert_eq!(utc(951_786_123), "2000-02-29 010203");
+        assert_eq!(utc(1_700_000_000), "2023-11-14 221320");
+        assert_eq!(utc(4_107_542_399), "2100-02-28 235959");
+    }
+}
@@ -0,0 +1,118 @@
+use crate::error::VxError;
+use crate::ffmpeg::capabilities::{select_encoder, H264_ENCODERS};
+use crate::ffmpeg::FfmpegRunner;
+use crate::ops::container::{movflags, pipe_args};
+use crate::ops::metadata::Metadata;
+use crate::utils::is_stdio;
+use std::path::Path;
+
+#[derive(Clone, Copy, Default)]
+pub enum Quality {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl Quality {
+    pub fn crf(self) -> u8 {
+        match self {
+            Quality::Low => 28,
+            Quality::Medium => 23,
+            Quality::High => 18,
+        }
+    }
+
+    pub fn bitrate(self) -> &'static str {
+        match self {
+            Quality::Low => "1M",
+            Quality::Medium => "2500k",
+            Quality::High => "5M",
+        }
+    }
+}
+
+// x264 encoder presets, fastest to slowest.
+#[derive(Clone, Copy, Default)]
+pub enum Speed {
+    Ultrafast,
+    Superfast,
+    Veryfast,
+    Faster,
+    Fast,
+    #[default]
+    Medium,
+    Slow,
+    Slower,
+    Veryslow,
+}
+
+impl Speed {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Speed::Ultrafast => "ultrafast",
+            Speed::Superfast => "superfast",
+            Speed::Veryfast => "veryfast",
+            Speed::Faster => "faster",
+            Speed::Fast => "fast",
+            Speed::Medium => "medium",
+            Speed::Slow => "slow",
+            Speed::Slower => "slower",
+            Speed::Veryslow => "veryslow",
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct CompressOptions {
+    pub quality: Quality,
+    /// Overrides the CRF implied by `quality` (libx264 only).
+    pub crf: Option<u8>,
+    pub speed: Speed,
+    pub metadata: Metadata,
+    /// Container format; taken from the output's extension when unset. Required for stdout.
+    pub format: Option<String>,
+}
+
+pub fn compress(
+    input: &Path,
+    output: &Path,
+    options: &CompressOptions,
+) -> Result<FfmpegRunner, VxError> {
+    let runner = FfmpegRunner::new()?;
+    let mut warnings = Vec::new();
+    let video = select_encoder(H264_ENCODERS, &mut warnings)?;
+    let crf = options.crf.unwrap_or(options.quality.crf()).to_string();
+
+    // Only libx264 understands -crf/-preset; fallback encoders get a target bitrate instead.
+    let rate_control = match video {
+        "libx264" => ["-crf", &crf, "-preset", options.speed.as_str()],
+        _ => ["-b:v", options.quality.bitrate(), "-maxrate", options.quality.bitrate()],
+    };
+
+    let format = match options.format {
+        Some(ref format) => format.to_lowercase(),
+        None if is_stdio(output) => return Err(VxError::StdoutNeedsFormat),
+        None => output.extension().unwrap_or_default().to_string_lossy().to_lowercase(),
+    };
+
+    let runner = runner
+        .input(input)
+        .args(["-c:v", video])
+        .args(rate_control)
+        .args([
+            "-c:a", "aac",
+            "-b:a", "128k",
+        ])
+        .args(movflags(&format, output, options.metadata, true))
+        .args(options.metadata.args())
+        .args(pipe_args(&format, output))
+        .output(output)
+        .overwrite()
+        .warn(warnings);
+
+    Ok(match options.metadata {
+        Metadata::Keep => runner.copy_file_times(input),
+        Metadata::Strip => runner,
+    })
+}
@@ -0,0 +1,48 @@
+use crate::ops::metadata::Metadata;
+use crate::utils::is_stdio;
+use std::path::Path;
+
+/// `-f` for outputs going to stdout, where ffmpeg has no file name to guess the format from.
+pub(crate) fn pipe_args(format: &str, output: &Path) -> Vec<String> {
+    if !is_stdio(output) {
+        return vec![];
+    }
+
+    let muxer = match format {
+        "mkv" => "matroska",
+        "m4v" => "mp4",
+        "mpg" | "mpeg" => "mpeg",
+        "ts" | "mts" | "m2ts" => "mpegts",
+        format => format,
+    };
+    vec!["-f".to_string(), muxer.to_string()]
+}
+
+/// `-movflags` for the QuickTime family. A pipe can't be seeked back into to write the index, so
+/// those outputs are fragmented instead.
+pub(crate) fn movflags(
+    format: &str,
+    output: &Path,
+    metadata: Metadata,
+    faststart: bool,
+) -> Vec<String> {
+    if !matches!(format, "mp4" | "m4v" | "mov" | "3gp") {
+        return vec![];
+    }
+
+    let mut flags = String::new();
+    if is_stdio(output) {
+        flags.push_str("+frag_keyframe+empty_moov+default_base_moof");
+    } else if faststart {
+        flags.push_str("+faststart");
+    }
+    // Otherwise the muxer drops keys it doesn't know, like the phone's location.
+    if metadata == Metadata::Keep {
+        flags.push_str("+use_metadata_tags");
+    }
+
+    match flags.is_empty() {
+        true => vec![],
+        false => vec!["-movflags".to_string(), flags],
+    }
+}
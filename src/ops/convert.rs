@@ -0,0 +1,104 @@
+use crate::error::{VxError, SUPPORTED_FORMATS};
+use crate::ffmpeg::capabilities::{
+    require_filters, select_encoder, H264_ENCODERS, MP3_ENCODERS, OPUS_ENCODERS, VP9_ENCODERS,
+};
+use crate::ffmpeg::FfmpegRunner;
+use crate::ops::compress::Speed;
+use crate::ops::container::{movflags, pipe_args};
+use crate::ops::gif::{palette_filter, GifOptions};
+use crate::ops::metadata::Metadata;
+use std::path::Path;
+
+pub struct ConvertOptions {
+    pub format: String,
+    /// Constant quality for libx264 and libvpx-vp9; encoder default when unset.
+    pub crf: Option<u8>,
+    /// x264 preset; ignored by other encoders.
+    pub speed: Option<Speed>,
+    pub metadata: Metadata,
+}
+
+impl ConvertOptions {
+    pub fn new(format: &str) -> Result<Self, VxError> {
+        let format = format.to_lowercase();
+
+        if !SUPPORTED_FORMATS.contains(&format.as_str()) {
+            return Err(VxError::UnsupportedFormat {
+                format,
+                supported: SUPPORTED_FORMATS.to_vec(),
+            });
+        }
+
+        Ok(Self { format, crf: None, speed: None, metadata: Metadata::default() })
+    }
+}
+
+pub fn convert(
+    input: &Path,
+    output: &Path,
+    options: &ConvertOptions,
+) -> Result<FfmpegRunner, VxError> {
+    let runner = FfmpegRunner::new()?.input(input);
+    let mut warnings = Vec::new();
+
+    let runner = match options.format.as_str() {
+        "gif" => {
+            let filter = palette_filter(&GifOptions::default());
+            require_filters(filter.filter_names())?;
+            runner.video_filter(&filter)
+        }
+        format => runner.args(get_codec_args(format, options, &mut warnings)?),
+    };
+
+    let runner = runner
+        .args(movflags(&options.format, output, options.metadata, false))
+        .args(options.metadata.args())
+        .args(pipe_args(&options.format, output))
+        .output(output)
+        .overwrite()
+        .warn(warnings);
+    Ok(match options.metadata {
+        Metadata::Keep => runner.copy_file_times(input),
+        Metadata::Strip => runner,
+    })
+}
+
+fn get_codec_args(
+    format: &str,
+    options: &ConvertOptions,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<String>, VxError> {
+    let mut select = |candidates| select_encoder(candidates, warnings);
+    let (video, audio) = match format {
+        "mp4" | "mov" => (select(H264_ENCODERS)?, "aac"),
+        "webm" => (select(VP9_ENCODERS)?, select(OPUS_ENCODERS)?),
+        "avi" => ("mpeg4", select(MP3_ENCODERS)?),
+        _ => return Ok(vec![]),
+    };
+
+    let mut args = vec!["-c:v".to_string(), video.to_string()];
+
+    match (video, options.crf) {
+        ("libx264", Some(crf)) => args.extend(["-crf".to_string(), crf.to_string()]),
+        // libvpx-vp9 only runs in constant-quality mode when the bitrate is zero.
+        ("libvpx-vp9", Some(crf)) => args.extend([
+            "-crf".to_string(),
+            crf.to_string(),
+            "-b:v".to_string(),
+            "0".to_string(),
+        ]),
+        _ => {}
+    }
+    if let ("libx264", Some(speed)) = (video, options.speed) {
+        args.extend(["-preset".to_string(), speed.as_str().to_string()]);
+    }
+
+    args.extend(["-c:a".to_string(), audio.to_string()]);
+    // ffmpeg's native opus and vorbis encoders are experimental, and vorbis only encodes stereo.
+    match audio {
+        "opus" => args.extend(["-strict", "experimental"].map(String::from)),
+        "vorbis" => args.extend(["-strict", "experimental", "-ac", "2"].map(String::from)),
+        _ => {}
+    }
+    Ok(args)
+}
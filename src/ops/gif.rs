@@ -0,0 +1,62 @@
+use crate::error::VxError;
+use crate::ffmpeg::capabilities::require_filters;
+use crate::ops::container::pipe_args;
+use crate::ffmpeg::{FfmpegRunner, Filter, FilterChain, FilterGraph};
+use std::path::Path;
+
+pub struct GifOptions {
+    pub width: u32,
+    pub fps: u32,
+    pub start: Option<f64>,
+    pub duration: Option<f64>,
+}
+
+impl Default for GifOptions {
+    fn default() -> Self {
+        Self { width: 480, fps: 10, start: None, duration: None }
+    }
+}
+
+impl GifOptions {
+    pub fn effective_duration(&self, total: f64) -> f64 {
+        let remaining = total - self.start.unwrap_or(0.0);
+        match self.duration {
+            Some(dur) => remaining.min(dur),
+            None => remaining,
+        }
+    }
+}
+
+pub fn gif(input: &Path, output: &Path, options: &GifOptions) -> Result<FfmpegRunner, VxError> {
+    let mut runner = FfmpegRunner::new()?.overwrite();
+    let filter = palette_filter(options);
+    require_filters(filter.filter_names())?;
+
+    if let Some(start) = options.start {
+        runner = runner.args(["-ss", &start.to_string()]);
+    }
+
+    runner = runner.input(input);
+
+    if let Some(dur) = options.duration {
+        runner = runner.args(["-t", &dur.to_string()]);
+    }
+
+    Ok(runner.video_filter(&filter).args(pipe_args("gif", output)).output(output))
+}
+
+pub(crate) fn palette_filter(options: &GifOptions) -> FilterGraph {
+    let scale = Filter::new("scale").arg(options.width).arg(-1).option("flags", "lanczos");
+
+    FilterGraph::new()
+        .chain(
+            FilterChain::new()
+                .filter(Filter::new("fps").arg(options.fps))
+                .filter(scale)
+                .filter(Filter::split(2))
+                .output("s0")
+                .output("s1"),
+        )
+        .chain(FilterChain::new().input("s0").filter(Filter::new("palettegen")).output("p"))
+        .chain(FilterChain::new().input("s1").input("p").filter(Filter::new("paletteuse")))
+}
@@ -0,0 +1,18 @@
+/// What happens to the source's tags (creation time, location, rotation, ...) and file times.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum Metadata {
+    /// Copy global and stream tags, and the input's modified/accessed times.
+    #[default]
+    Keep,
+    /// Drop all tags and chapters.
+    Strip,
+}
+
+impl Metadata {
+    pub fn args(self) -> [&'static str; 4] {
+        match self {
+            Metadata::Keep => ["-map_metadata", "0", "-map_chapters", "0"],
+            Metadata::Strip => ["-map_metadata", "-1", "-map_chapters", "-1"],
+        }
+    }
+}
@@ -0,0 +1,10 @@
+mod compress;
+mod container;
+mod convert;
+mod gif;
+mod metadata;
+
+pub use compress::{compress, CompressOptions, Quality, Speed};
+pub use convert::{convert, ConvertOptions};
+pub use gif::{gif, GifOptions};
+pub use metadata::Metadata;
@@ -0,0 +1,307 @@
+use crate::cli::{Cli, Commands, PresetAction, PresetArgs, PresetCommand, Quality, Speed};
+use crate::config::Config;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use toml::Value;
+use video_express::VxError;
+
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Preset {
+    pub command: PresetCommand,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fps: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quality: Option<Quality>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crf: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speed: Option<Speed>,
+}
+
+impl Preset {
+    fn new(command: PresetCommand) -> Self {
+        Self {
+            command,
+            width: None,
+            fps: None,
+            duration: None,
+            quality: None,
+            crf: None,
+            speed: None,
+        }
+    }
+
+    fn validate(&self, name: &str) -> Result<(), VxError> {
+        let invalid = |message: String| VxError::InvalidPreset { name: name.to_string(), message };
+
+        let allowed: &[&str] = match self.command {
+            PresetCommand::Gif => &["width", "fps", "duration"],
+            PresetCommand::Compress => &["quality", "crf", "speed"],
+            PresetCommand::To => &["crf", "speed"],
+        };
+        let set = [
+            ("width", self.width.is_some()),
+            ("fps", self.fps.is_some()),
+            ("duration", self.duration.is_some()),
+            ("quality", self.quality.is_some()),
+            ("crf", self.crf.is_some()),
+            ("speed", self.speed.is_some()),
+        ];
+        if let Some((field, _)) = set.iter().find(|(field, set)| *set && !allowed.contains(field)) {
+            return Err(invalid(format!(
+                "`{field}` does not apply to `vx {}` (allowed: {})",
+                command_name(self.command),
+                allowed.join(", ")
+            )));
+        }
+
+        let max_crf = match self.command {
+            PresetCommand::Compress => 51,
+            _ => 63,
+        };
+        if let Some(crf) = self.crf.filter(|crf| *crf > max_crf) {
+            return Err(invalid(format!("crf {crf} is out of range (0-{max_crf})")));
+        }
+
+        Ok(())
+    }
+
+    fn summary(&self) -> String {
+        let Ok(Value::Table(mut table)) = Value::try_from(self) else {
+            return String::new();
+        };
+        table.remove("command");
+        table
+            .iter()
+            .map(|(key, value)| {
+                format!(
+                    "{key}={}",
+                    value.as_str().map_or(value.to_string(), str::to_string)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+fn builtin() -> BTreeMap<String, Preset> {
+    let presets = [
+        (
+            "slack-gif",
+            Preset {
+                width: Some(640),
+                fps: Some(12),
+                duration: Some(6.0),
+                ..Preset::new(PresetCommand::Gif)
+            },
+        ),
+        (
+            "small-gif",
+            Preset {
+                width: Some(320),
+                fps: Some(8),
+                ..Preset::new(PresetCommand::Gif)
+            },
+        ),
+        (
+            "archive",
+            Preset {
+                crf: Some(18),
+                speed: Some(Speed::Slow),
+                ..Preset::new(PresetCommand::Compress)
+            },
+        ),
+        (
+            "share",
+            Preset {
+                quality: Some(Quality::Low),
+                speed: Some(Speed::Fast),
+                ..Preset::new(PresetCommand::Compress)
+            },
+        ),
+    ];
+
+    presets.into_iter().map(|(name, preset)| (name.to_string(), preset)).collect()
+}
+
+// Saved presets live next to the user config so they follow VX_CONFIG / XDG_CONFIG_HOME.
+pub fn path() -> Option<PathBuf> {
+    Config::user_path().map(|config| config.with_file_name("presets.toml"))
+}
+
+fn load_saved() -> Result<BTreeMap<String, Preset>> {
+    let Some(path) = path().filter(|p| p.exists()) else {
+        return Ok(BTreeMap::new());
+    };
+
+    let invalid = |message: String| VxError::InvalidConfig { path: path.clone(), message };
+    let content = std::fs::read_to_string(&path)?;
+    let presets: BTreeMap<String, Preset> =
+        toml::from_str(&content).map_err(|err| invalid(err.message().to_string()))?;
+
+    for (name, preset) in &presets {
+        preset.validate(name).map_err(|err| invalid(err.to_string()))?;
+    }
+
+    Ok(presets)
+}
+
+fn save(presets: &BTreeMap<String, Preset>, path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, toml::to_string(presets)?)?;
+    Ok(())
+}
+
+// Saved presets shadow built-ins with the same name.
+fn find(name: &str) -> Result<Preset> {
+    let mut saved = load_saved()?;
+    if let Some(preset) = saved.remove(name) {
+        return Ok(preset);
+    }
+
+    let mut builtin = builtin();
+    if let Some(preset) = builtin.remove(name) {
+        return Ok(preset);
+    }
+
+    let mut available: Vec<String> = saved.into_keys().chain(builtin.into_keys()).collect();
+    available.sort();
+    available.dedup();
+    Err(VxError::PresetNotFound { name: name.to_string(), available }.into())
+}
+
+fn resolve(name: &Option<String>, command: PresetCommand) -> Result<Option<Preset>> {
+    let Some(name) = name else {
+        return Ok(None);
+    };
+
+    let preset = find(name)?;
+    if preset.command != command {
+        return Err(VxError::PresetMismatch {
+            name: name.clone(),
+            expected: command_name(preset.command).to_string(),
+            command: command_name(command).to_string(),
+        }
+        .into());
+    }
+
+    Ok(Some(preset))
+}
+
+/// Fills flags the user left unset from `--preset`; runs before config defaults are applied.
+pub fn apply(cli: &mut Cli) -> Result<()> {
+    match &mut cli.command {
+        Commands::Gif(args) => {
+            if let Some(preset) = resolve(&args.preset, PresetCommand::Gif)? {
+                args.width = args.width.or(preset.width);
+                args.fps = args.fps.or(preset.fps);
+                args.duration = args.duration.or(preset.duration);
+            }
+        }
+        Commands::Compress(args) => {
+            if let Some(preset) = resolve(&args.preset, PresetCommand::Compress)? {
+                // A -q from the command line sets the CRF itself; only --crf overrides it.
+                if args.quality.is_none() {
+                    args.crf = args.crf.or(preset.crf);
+                }
+                args.quality = args.quality.or(preset.quality);
+                args.speed = args.speed.or(preset.speed);
+            }
+        }
+        Commands::To(args) => {
+            if let Some(preset) = resolve(&args.preset, PresetCommand::To)? {
+                args.crf = args.crf.or(preset.crf);
+                args.speed = args.speed.or(preset.speed);
+            }
+        }
+        Commands::Info(_) | Commands::Config(_) | Commands::Preset(_) | Commands::Watch(_) => {}
+    }
+
+    Ok(())
+}
+
+pub fn execute(args: PresetArgs) -> Result<()> {
+    match args.action {
+        PresetAction::Save { name, command, width, fps, duration, quality, crf, speed } => {
+            let valid = |c: char| c.is_ascii_alphanumeric() || "-_".contains(c);
+            if name.is_empty() || !name.chars().all(valid) {
+                return Err(VxError::InvalidPreset {
+                    name,
+                    message: "names may only contain letters, digits, '-' and '_'".to_string(),
+                }
+                .into());
+            }
+
+            let preset = Preset { command, width, fps, duration, quality, crf, speed };
+            preset.validate(&name)?;
+
+            let path = path().ok_or_else(|| VxError::InvalidConfig {
+                path: PathBuf::from("~/.config/vx/presets.toml"),
+                message: "cannot determine the home directory".to_string(),
+            })?;
+
+            let mut presets = load_saved()?;
+            presets.insert(name.clone(), preset);
+            save(&presets, &path)?;
+
+            let note = match builtin().contains_key(&name) {
+                true => " (overrides the built-in preset)",
+                false => "",
+            };
+            println!("Saved preset '{name}' to {}{note}", path.display());
+        }
+        PresetAction::List => {
+            let saved = load_saved()?;
+            let mut all: BTreeMap<String, (Preset, &str)> =
+                builtin().into_iter().map(|(name, preset)| (name, (preset, "built-in"))).collect();
+            all.extend(saved.into_iter().map(|(name, preset)| (name, (preset, "saved"))));
+
+            let width = all.keys().map(String::len).max().unwrap_or(0);
+            for (name, (preset, source)) in &all {
+                println!(
+                    "{name:<width$}  {:<8}  {}  ({source})",
+                    command_name(preset.command),
+                    preset.summary()
+                );
+            }
+        }
+        PresetAction::Show { name } => {
+            let preset = find(&name)?;
+            print!("{}", toml::to_string(&preset)?);
+        }
+        PresetAction::Delete { name } => {
+            let mut presets = load_saved()?;
+            if presets.remove(&name).is_none() {
+                let message = match builtin().contains_key(&name) {
+                    true => "built-in presets cannot be deleted".to_string(),
+                    false => "no saved preset with this name".to_string(),
+                };
+                return Err(VxError::InvalidPreset { name, message }.into());
+            }
+
+            if let Some(path) = path() {
+                save(&presets, &path)?;
+            }
+            println!("Deleted preset '{name}'");
+        }
+    }
+
+    Ok(())
+}
+
+fn command_name(command: PresetCommand) -> &'static str {
+    match command {
+        PresetCommand::Gif => "gif",
+        PresetCommand::Compress => "compress",
+        PresetCommand::To => "to",
+    }
+}
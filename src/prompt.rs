@@ -0,0 +1,28 @@
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+
+pub fn confirm_overwrite(path: &Path) -> bool {
+    if !path.exists() {
+        return true;
+    }
+
+    confirm(&format!("{} already exists. Overwrite?", path.display()))
+}
+
+/// Asks a yes/no question on the terminal, defaulting to no.
+pub fn confirm(question: &str) -> bool {
+    // Nobody is there to answer; treat it as a "no" instead of waiting forever.
+    if !io::stdin().is_terminal() {
+        return false;
+    }
+
+    print!("{question} [y/N] ");
+    io::stdout().flush().ok();
+
+    let mut input = String::new();
+    if io::stdin().read_line(&mut input).is_err() {
+        return false;
+    }
+
+    matches!(input.trim().to_lowercase().as_str(), "y" | "yes")
+}
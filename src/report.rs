@@ -0,0 +1,123 @@
+use crate::cli::{ErrorFormat, GlobalArgs, ProgressMode};
+use crate::error_output;
+use crate::prompt::confirm;
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::path::Path;
+use std::time::Duration;
+use video_express::utils::{format_bitrate, format_duration, format_size};
+use video_express::{get_video_info, VideoInfo};
+
+/// A finished single-file job, compared against its input after `Created: ...`.
+pub struct Report<'a> {
+    pub input: &'a Path,
+    pub output: &'a Path,
+    pub elapsed: Duration,
+    /// Media duration that was encoded, used when the output can't be probed.
+    pub duration: Option<f64>,
+}
+
+impl Report<'_> {
+    pub fn print(&self, global: &GlobalArgs, warn_if_larger: bool) -> Result<()> {
+        let input_size = std::fs::metadata(self.input).map(|m| m.len()).unwrap_or(0);
+        let output_size = std::fs::metadata(self.output).map(|m| m.len()).unwrap_or(0);
+        // Probing is best effort: sizes and timings are still worth reporting without it.
+        let before = get_video_info(self.input).ok();
+        let after = get_video_info(self.output).ok();
+
+        let duration = after.as_ref().map(|i| i.duration).filter(|d| *d > 0.0).or(self.duration);
+        let elapsed = self.elapsed.as_secs_f64();
+        let speed = duration.filter(|_| elapsed > 0.0).map(|d| d / elapsed);
+        let larger = warn_if_larger && output_size > input_size;
+
+        if let ProgressMode::Json = global.progress {
+            let event = json!({
+                "event": "report",
+                "input_size": input_size,
+                "output_size": output_size,
+                "change": percent(input_size, output_size),
+                "elapsed": elapsed,
+                "speed": speed,
+                "before": before.as_ref().map(stream_json),
+                "after": after.as_ref().map(stream_json),
+                "larger": larger,
+            });
+            println!("{event}");
+            return Ok(());
+        }
+
+        let size = format!("{} → {}", format_size(input_size), format_size(output_size));
+        println!("  Size        : {size}{}", change(input_size, output_size));
+
+        if let (Some(before), Some(after)) = (&before, &after) {
+            let resolution = |i: &VideoInfo| format!("{}x{}", i.width, i.height);
+            let fps = |i: &VideoInfo| format!("{:.0}", i.fps);
+            let codec = |i: &VideoInfo| i.video_codec.to_uppercase();
+            let bitrate = |i: &VideoInfo| format_bitrate(i.bitrate);
+
+            // Only what the encode changed, to keep the report short.
+            for (label, field) in [
+                ("Resolution", &resolution as &dyn Fn(&VideoInfo) -> String),
+                ("FPS", &fps),
+                ("Codec", &codec),
+                ("Bitrate", &bitrate),
+            ] {
+                let (from, to) = (field(before), field(after));
+                if from != to {
+                    println!("  {label:<12}: {from} → {to}");
+                }
+            }
+        }
+
+        let realtime = speed.map(|s| format!(" ({s:.1}x realtime)")).unwrap_or_default();
+        println!("  Time        : {}{realtime}", format_duration(elapsed));
+
+        if larger {
+            self.offer_original(input_size, output_size, global)?;
+        }
+        Ok(())
+    }
+
+    fn offer_original(&self, input_size: u64, output_size: u64, global: &GlobalArgs) -> Result<()> {
+        let warning = format!(
+            "the output is LARGER than the original ({} → {}, {:+.1}%)",
+            format_size(input_size),
+            format_size(output_size),
+            percent(input_size, output_size).unwrap_or(0.0)
+        );
+        match global.error_format {
+            ErrorFormat::Text => {
+                eprintln!();
+                error_output::warn(&warning, global.error_format);
+                eprintln!("  Try a lower --quality, a higher --crf or a slower --speed");
+            }
+            ErrorFormat::Json => error_output::warn(&warning, global.error_format),
+        }
+
+        // `confirm` only asks when stdin is a terminal.
+        if !global.unattended && confirm("Keep the original and delete the output?") {
+            std::fs::remove_file(self.output)?;
+            println!("Deleted: {}", self.output.display());
+        }
+        Ok(())
+    }
+}
+
+/// Size change as " (-76.9%)", or nothing when the input size is unknown.
+pub fn change(before: u64, after: u64) -> String {
+    percent(before, after).map(|p| format!(" ({p:+.1}%)")).unwrap_or_default()
+}
+
+fn percent(before: u64, after: u64) -> Option<f64> {
+    (before > 0).then(|| (after as f64 - before as f64) / before as f64 * 100.0)
+}
+
+fn stream_json(info: &VideoInfo) -> Value {
+    json!({
+        "width": info.width,
+        "height": info.height,
+        "fps": info.fps,
+        "codec": info.video_codec,
+        "bitrate": info.bitrate,
+    })
+}
@@ -1,6 +1,6 @@
 use crate::error::VxError;
-use std::io::{self, Write};
-use std::path::Path;
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
 
 pub fn parse_time(s: &str) -> Result<f64, VxError> {
     if let Ok(secs) = s.parse::<f64>() {
@@ -67,30 +67,274 @@ pub fn format_bitrate(bps: u64) -> String {
     }
 }
 
-pub fn confirm_overwrite(path: &Path) -> bool {
-    if !path.exists() {
-        return true;
-    }
-
-    print!("{} already exists. Overwrite? [y/N] ", path.display());
-    io::stdout().flush().ok();
+/// `-` stands for stdin as an input and stdout as an output.
+pub fn is_stdio(path: &Path) -> bool {
+    path.as_os_str() == "-"
+}
 
-    let mut input = String::new();
-    if io::stdin().read_line(&mut input).is_err() {
+/// Whether the input is something ffmpeg reads over the network (`https://`, `rtsp://`, ...).
+pub fn is_url(path: &Path) -> bool {
+    let Some((scheme, _)) = path.to_str().and_then(|s| s.split_once("://")) else {
         return false;
+    };
+    // Single letters are Windows drives, not schemes.
+    scheme.len() > 1
+        && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+/// Last segment of a URL's path, percent-decoded, e.g. `clip.mp4` for
+/// `https://example.com/media/clip.mp4?token=1`. `None` when the path is empty.
+pub fn url_file_name(url: &str) -> Option<String> {
+    let (_, rest) = url.split_once("://")?;
+    let path = rest.split(['?', '#']).next().unwrap_or_default();
+    let (_, path) = path.split_once('/')?;
+    let name = path.rsplit('/').next().unwrap_or_default();
+
+    let raw = name.as_bytes();
+    let mut bytes = Vec::with_capacity(raw.len());
+    let mut i = 0;
+    while i < raw.len() {
+        let hex = raw.get(i + 1..i + 3).and_then(|h| std::str::from_utf8(h).ok());
+        match hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+            Some(byte) if raw[i] == b'%' => {
+                bytes.push(byte);
+                i += 3;
+            }
+            _ => {
+                bytes.push(raw[i]);
+                i += 1;
+            }
+        }
     }
 
-    matches!(input.trim().to_lowercase().as_str(), "y" | "yes")
+    let name = String::from_utf8_lossy(&bytes).replace(['/', '\\'], "_");
+    (!name.is_empty()).then_some(name)
 }
 
-pub fn default_output_path(input: &Path, suffix: Option<&str>, new_ext: &str) -> std::path::PathBuf {
-    let stem = input.file_stem().unwrap_or_default().to_string_lossy();
+/// First of `path`, `name (1).ext`, `name (2).ext`, ... that does not exist yet.
+pub fn unique_path(path: &Path) -> PathBuf {
+    unique_path_by(path, Path::exists)
+}
+
+/// Like `unique_path`, with `taken` deciding which names are in use.
+pub fn unique_path_by(path: &Path, taken: impl Fn(&Path) -> bool) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default();
+    // "clip (1).gif" continues as "clip (2).gif" rather than "clip (1) (1).gif".
+    let counted = stem.to_str().and_then(|stem| {
+        let (base, n) = stem.strip_suffix(')')?.rsplit_once(" (")?;
+        let n = n.parse::<u32>().ok().filter(|_| n.bytes().all(|b| b.is_ascii_digit()))?;
+        Some((OsStr::new(base), n + 1))
+    });
+    let (base, mut n) = counted.unwrap_or((stem, 1));
+
+    let mut candidate = path.to_path_buf();
+    while taken(&candidate) {
+        let mut name = base.to_os_string();
+        name.push(format!(" ({n})"));
+        if let Some(ext) = path.extension() {
+            name.push(".");
+            name.push(ext);
+        }
+        candidate = path.with_file_name(name);
+        n += 1;
+    }
+    candidate
+}
+
+// Built from OsStr pieces so names that aren't valid UTF-8 keep their exact bytes.
+pub fn default_output_path(input: &Path, suffix: Option<&str>, new_ext: &str) -> PathBuf {
     let parent = input.parent().unwrap_or(Path::new("."));
 
-    let filename = match suffix {
-        Some(s) => format!("{stem}{s}.{new_ext}"),
-        None => format!("{stem}.{new_ext}"),
-    };
+    let mut filename = input.file_stem().unwrap_or_default().to_os_string();
+    filename.push(suffix.unwrap_or_default());
+    filename.push(".");
+    filename.push(new_ext);
 
     parent.join(filename)
 }
+
+/// Replaces `{name}` placeholders using `lookup`; `{name:N}` zero-pads the value to N characters
+/// and `{{` / `}}` produce literal braces. Placeholders `lookup` doesn't know are an error.
+pub fn render_template<F, E>(template: &str, mut lookup: F) -> Result<OsString, E>
+where
+    F: FnMut(&str) -> Result<Option<OsString>, E>,
+    E: From<VxError>,
+{
+    let invalid =
+        |message: String| VxError::InvalidTemplate { template: template.to_string(), message };
+
+    let mut out = OsString::new();
+    let mut rest = template;
+
+    while let Some(i) = rest.find(['{', '}']) {
+        out.push(&rest[..i]);
+        let tail = &rest[i..];
+
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            out.push(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+        if tail.starts_with('}') {
+            return Err(invalid("unmatched '}'".to_string()).into());
+        }
+
+        let end = tail.find('}').ok_or_else(|| invalid("unclosed '{'".to_string()))?;
+        let (name, width) = match tail[1..end].split_once(':') {
+            Some((name, width)) => {
+                let width = width
+                    .parse::<usize>()
+                    .map_err(|_| invalid(format!("bad width in {{{}}}", &tail[1..end])))?;
+                (name, width)
+            }
+            None => (&tail[1..end], 0),
+        };
+
+        let value = lookup(name)?.ok_or_else(|| invalid(format!("unknown placeholder {{{name}}}")))?;
+        let len = value.to_string_lossy().chars().count();
+        out.push("0".repeat(width.saturating_sub(len)));
+        out.push(value);
+        rest = &tail[end + 1..];
+    }
+
+    out.push(rest);
+    Ok(out)
+}
+
+pub fn shell_quote(arg: &OsStr) -> String {
+    let Some(arg) = arg.to_str() else {
+        // Not UTF-8: spell the bytes out with ANSI-C quoting so the command still pastes as is.
+        let escaped: String = arg
+            .as_encoded_bytes()
+            .iter()
+            .map(|&b| match b {
+                b' '..=b'~' if b != b'\'' && b != b'\\' => (b as char).to_string(),
+                _ => format!("\\x{b:02x}"),
+            })
+            .collect();
+        return format!("$'{escaped}'");
+    };
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "-_=+/.,:@%^".contains(c);
+
+    if !arg.is_empty() && arg.chars().all(is_safe) {
+        return arg.to_string();
+    }
+
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(unix)]
+    use std::os::unix::ffi::OsStrExt;
+
+    #[cfg(unix)]
+    fn bytes(b: &[u8]) -> &Path {
+        Path::new(OsStr::from_bytes(b))
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn default_output_keeps_non_utf8_bytes() {
+        let output = default_output_path(bytes(b"in/clip\xff.mov"), Some("_compressed"), "mp4");
+        assert_eq!(output, bytes(b"in/clip\xff_compressed.mp4"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn rename_candidate_keeps_non_utf8_bytes() {
+        let taken = bytes(b"in/clip\xff.gif");
+        let output = unique_path_by(taken, |path| path == taken);
+        assert_eq!(output, bytes(b"in/clip\xff (1).gif"));
+    }
+
+    #[test]
+    fn rename_continues_an_existing_counter() {
+        let taken = Path::new("clip (1).gif");
+        assert_eq!(unique_path_by(taken, |path| path == taken), Path::new("clip (2).gif"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn template_keeps_non_utf8_values() {
+        let rendered = render_template("{stem}_{n:3}.gif", |key| -> Result<_, VxError> {
+            Ok(match key {
+                "stem" => Some(OsStr::from_bytes(b"caf\xe9").to_os_string()),
+                "n" => Some(OsString::from("7")),
+                _ => None,
+            })
+        });
+        assert_eq!(rendered.unwrap(), OsStr::from_bytes(b"caf\xe9_007.gif"));
+    }
+
+    fn template_error(template: &str) -> String {
+        let lookup = |_: &str| -> Result<_, VxError> { Ok(Some(OsString::from("7"))) };
+        match render_template(template, lookup) {
+            Err(VxError::InvalidTemplate { message, .. }) => message,
+            other => panic!("expected an invalid template, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn template_reports_malformed_placeholders() {
+        assert_eq!(template_error("{stem.gif"), "unclosed '{'");
+        assert_eq!(template_error("{n:x}.gif"), "bad width in {n:x}");
+        assert_eq!(template_error("{n:-1}.gif"), "bad width in {n:-1}");
+        assert_eq!(template_error("stem}.gif"), "unmatched '}'");
+    }
+
+    #[test]
+    fn template_escapes_braces_and_rejects_unknown_placeholders() {
+        let lookup = |key: &str| -> Result<_, VxError> {
+            Ok((key == "n").then(|| OsString::from("7")))
+        };
+        assert_eq!(render_template("{{{n}}}.gif", lookup).unwrap(), "{7}.gif");
+        let Err(VxError::InvalidTemplate { message, .. }) = render_template("{nope}.gif", lookup)
+        else {
+            panic!("expected an unknown placeholder");
+        };
+        assert_eq!(message, "unknown placeholder {nope}");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn shell_quote_spells_out_non_utf8_bytes() {
+        let arg = OsStr::from_bytes(b"caf\xe9 clip.mov");
+        assert_eq!(shell_quote(arg), r"$'caf\xe9 clip.mov'");
+        assert_eq!(shell_quote(OsStr::new("it's.mp4")), r"'it'\''s.mp4'");
+    }
+
+    #[test]
+    fn detects_url_schemes() {
+        assert!(is_url(Path::new("https://example.com/clip.mp4")));
+        assert!(is_url(Path::new("rtsp://camera.local/stream")));
+        assert!(is_url(Path::new("srt+udp://host:9000")));
+        assert!(!is_url(Path::new("clip.mp4")));
+        assert!(!is_url(Path::new("C://videos/clip.mp4")));
+        assert!(!is_url(Path::new("1http://example.com/clip.mp4")));
+        assert!(!is_url(Path::new("./out/a://b.mp4")));
+    }
+
+    #[test]
+    fn url_file_name_ignores_query_and_fragment() {
+        let url = "https://example.com/media/my%20clip.mp4?token=a/b.mov#t=10";
+        assert_eq!(url_file_name(url).as_deref(), Some("my clip.mp4"));
+        assert_eq!(url_file_name("https://example.com/a.webm#x").as_deref(), Some("a.webm"));
+    }
+
+    #[test]
+    fn url_file_name_keeps_decoded_separators_out() {
+        let url = "https://example.com/a%2Fb%5Cc.mp4";
+        assert_eq!(url_file_name(url).as_deref(), Some("a_b_c.mp4"));
+        assert_eq!(url_file_name("https://example.com/100%.mp4").as_deref(), Some("100%.mp4"));
+    }
+
+    #[test]
+    fn url_without_a_path_has_no_file_name() {
+        assert_eq!(url_file_name("https://example.com"), None);
+        assert_eq!(url_file_name("https://example.com/"), None);
+        assert_eq!(url_file_name("https://example.com/live/?id=3"), None);
+    }
+}